@@ -1,5 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use obr::*;
+use rustc_hash::FxBuildHasher;
 
 const TEST_FILE: &str = "data/measurements_1KRecords.txt";
 
@@ -75,7 +76,30 @@ pub fn bench_parsing(c: &mut Criterion) {
     
     group.bench_function("unsafe_parsing", |b| {
         b.iter(|| {
-            parse_records_unsafe(black_box(&lines))
+            // SAFETY: the sample file is valid UTF-8
+            unsafe { parse_records_unsafe(black_box(&lines)) }
+                .expect("Failed to parse records")
+        })
+    });
+    
+    group.bench_function("byte_based_ref", |b| {
+        b.iter(|| {
+            parse_records_bytes_ref(black_box(&lines))
+                .expect("Failed to parse records")
+        })
+    });
+    
+    group.bench_function("unsafe_parsing_ref", |b| {
+        b.iter(|| {
+            // SAFETY: the sample file is valid UTF-8
+            unsafe { parse_records_unsafe_ref(black_box(&lines)) }
+                .expect("Failed to parse records")
+        })
+    });
+    
+    group.bench_function("fixed_point", |b| {
+        b.iter(|| {
+            parse_records_fixed(black_box(&lines))
                 .expect("Failed to parse records")
         })
     });
@@ -91,6 +115,8 @@ pub fn bench_aggregation(c: &mut Criterion) {
     let data = read_file_raw_buffered(TEST_FILE).expect("Failed to read test file");
     let lines = split_into_lines_simd(&data);
     let records = parse_records_bytes(&lines).expect("Failed to parse records");
+    let ref_records = parse_records_bytes_ref(&lines).expect("Failed to parse records");
+    let fixed_records = parse_records_fixed(&lines).expect("Failed to parse records");
     let mut group = c.benchmark_group("aggregation");
     
     group.bench_function("std_hashmap", |b| {
@@ -105,12 +131,66 @@ pub fn bench_aggregation(c: &mut Criterion) {
         })
     });
     
+    group.bench_function("hash_table_xxhash32", |b| {
+        b.iter(|| {
+            aggregate_records_with_hasher::<XxHash32Builder>(black_box(&records))
+        })
+    });
+    
+    group.bench_function("hash_table_xxhash64", |b| {
+        b.iter(|| {
+            aggregate_records_with_hasher::<XxHash64Builder>(black_box(&records))
+        })
+    });
+    
+    group.bench_function("hash_table_xxh3_64", |b| {
+        b.iter(|| {
+            aggregate_records_with_hasher::<Xxh3_64Builder>(black_box(&records))
+        })
+    });
+    
+    group.bench_function("hash_table_xxh3_128", |b| {
+        b.iter(|| {
+            aggregate_records_with_hasher::<Xxh3_128Builder>(black_box(&records))
+        })
+    });
+    
+    group.bench_function("hash_table_fxhash", |b| {
+        b.iter(|| {
+            aggregate_records_with_hasher::<FxBuildHasher>(black_box(&records))
+        })
+    });
+    
     group.bench_function("streaming", |b| {
         b.iter(|| {
             aggregate_records_streaming(black_box(records.iter().cloned()))
         })
     });
     
+    group.bench_function("borrowed_ref", |b| {
+        b.iter(|| {
+            aggregate_records_ref(black_box(&ref_records))
+        })
+    });
+    
+    group.bench_function("byte_table_xxhash64", |b| {
+        b.iter(|| {
+            aggregate_records_byte_table::<XxHash64Builder>(black_box(&ref_records))
+        })
+    });
+    
+    group.bench_function("byte_table_xxh3", |b| {
+        b.iter(|| {
+            aggregate_records_byte_table::<Xxh3_64Builder>(black_box(&ref_records))
+        })
+    });
+    
+    group.bench_function("fixed_point", |b| {
+        b.iter(|| {
+            aggregate_records_fixed(black_box(&fixed_records))
+        })
+    });
+    
     group.finish();
 }
 
@@ -144,7 +224,44 @@ pub fn bench_full_pipeline(c: &mut Criterion) {
     
     group.bench_function("mmap_unsafe", |b| {
         b.iter(|| {
-            pipeline_mmap_unsafe(black_box(TEST_FILE))
+            // SAFETY: the sample file is valid UTF-8
+            unsafe { pipeline_mmap_unsafe(black_box(TEST_FILE)) }
+                .expect("Failed to process file")
+        })
+    });
+    
+    group.bench_function("mmap_bytes_ref", |b| {
+        b.iter(|| {
+            pipeline_mmap_bytes_ref(black_box(TEST_FILE))
+                .expect("Failed to process file")
+        })
+    });
+    
+    group.bench_function("mmap_unsafe_ref", |b| {
+        b.iter(|| {
+            // SAFETY: the sample file is valid UTF-8
+            unsafe { pipeline_mmap_unsafe_ref(black_box(TEST_FILE)) }
+                .expect("Failed to process file")
+        })
+    });
+    
+    group.bench_function("mmap_byte_table", |b| {
+        b.iter(|| {
+            pipeline_mmap_byte_table(black_box(TEST_FILE))
+                .expect("Failed to process file")
+        })
+    });
+    
+    group.bench_function("mmap_fused", |b| {
+        b.iter(|| {
+            pipeline_mmap_fused(black_box(TEST_FILE))
+                .expect("Failed to process file")
+        })
+    });
+    
+    group.bench_function("mmap_fixed", |b| {
+        b.iter(|| {
+            pipeline_mmap_fixed(black_box(TEST_FILE))
                 .expect("Failed to process file")
         })
     });
@@ -163,6 +280,14 @@ pub fn bench_full_pipeline(c: &mut Criterion) {
         })
     });
     
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    group.bench_function("parallel", |b| {
+        b.iter(|| {
+            pipeline_parallel(black_box(TEST_FILE), black_box(threads))
+                .expect("Failed to process file")
+        })
+    });
+    
     group.finish();
 }
 
@@ -213,6 +338,27 @@ pub fn bench_stage_timing(c: &mut Criterion) {
         })
     });
     
+    // Time file read + fused split/parse/hash in a single pass
+    group.bench_function("05_read_fused_scan", |b| {
+        b.iter(|| {
+            let mmap = read_file_raw_mmap(black_box(TEST_FILE))
+                .expect("Failed to mmap file");
+            let _records = FusedScanner::new(&mmap)
+                .collect::<Result<Vec<_>, _>>()
+                .expect("Failed to parse records");
+        })
+    });
+    
+    // Time complete fused pipeline (pre-hashed table lookups)
+    group.bench_function("06_fused_complete_pipeline", |b| {
+        b.iter(|| {
+            let mmap = read_file_raw_mmap(black_box(TEST_FILE))
+                .expect("Failed to mmap file");
+            let _stats = aggregate_records_fused(&mmap)
+                .expect("Failed to aggregate records");
+        })
+    });
+    
     group.finish();
 }
 
@@ -0,0 +1,130 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process;
+use obr::{
+    generate_stations, parse_station_list, write_corrupted_measurements, write_measurements, Corruption, CorruptionConfig, NameLength,
+    OutputSize, Rng, StationConfig,
+};
+
+const DEFAULT_ROWS: u64 = 1_000_000;
+const DEFAULT_SEED: u64 = 1;
+const DEFAULT_STD_DEVIATION: f64 = 10.0;
+
+fn print_usage(program: &str) {
+    eprintln!("Usage: {} [--rows N | --size BYTES[K|M|G]] [--seed N] [--stations N] [--name-length N|MIN-MAX]", program);
+    eprintln!("       {:width$} [--station-list FILE] [--std-dev X] [--output FILE]", "", width = program.len());
+    eprintln!("       {:width$} [--corrupt-rate R] [--corrupt KIND,...] [--manifest FILE]", "", width = program.len());
+    eprintln!("Example: {} --rows 1000000 --seed 1 --output data/measurements_1MRecords.txt", program);
+    eprintln!("Example: {} --size 1G --stations 10000 --name-length 1-50 > big.txt", program);
+    eprintln!("Example: {} --rows 10000 --corrupt-rate 0.01 --output fuzz.txt  (manifest in fuzz.txt.manifest)", program);
+    eprintln!("Corruption kinds: {}", Corruption::ALL.map(|c| c.name()).join(", "));
+}
+
+// "500", "64K", "10M", "1G" (powers of 1024)
+fn parse_size(value: &str) -> Option<u64> {
+    let (number, multiplier) = match value.as_bytes().last()?.to_ascii_uppercase() {
+        b'K' => (&value[..value.len() - 1], 1 << 10),
+        b'M' => (&value[..value.len() - 1], 1 << 20),
+        b'G' => (&value[..value.len() - 1], 1 << 30),
+        _ => (value, 1),
+    };
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("Error: {}", message);
+    process::exit(1);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut size = OutputSize::Rows(DEFAULT_ROWS);
+    let mut seed = DEFAULT_SEED;
+    let mut config = StationConfig::default();
+    let mut station_list: Option<String> = None;
+    let mut std_deviation = DEFAULT_STD_DEVIATION;
+    let mut output: Option<String> = None;
+    let mut corruption: Option<CorruptionConfig> = None;
+    let mut manifest_path: Option<String> = None;
+
+    let mut i = 1;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let value = || match args.get(i + 1) {
+            Some(value) => value.as_str(),
+            None => {
+                print_usage(&args[0]);
+                process::exit(1);
+            }
+        };
+
+        match flag {
+            "--rows" => size = OutputSize::Rows(value().parse().unwrap_or_else(|_| fail("--rows expects a non-negative integer"))),
+            "--size" => size = OutputSize::Bytes(parse_size(value()).unwrap_or_else(|| fail("--size expects a byte count such as 500, 64K, 10M or 1G"))),
+            "--seed" => seed = value().parse().unwrap_or_else(|_| fail("--seed expects a non-negative integer")),
+            "--stations" => {
+                config.count = value().parse().ok().filter(|&n| n > 0).unwrap_or_else(|| fail("--stations expects a positive integer"));
+            }
+            "--name-length" => config.name_length = value().parse::<NameLength>().unwrap_or_else(|e| fail(&e)),
+            "--station-list" => station_list = Some(value().to_string()),
+            "--std-dev" => {
+                std_deviation = value().parse().ok().filter(|&x: &f64| x.is_finite() && x >= 0.0)
+                    .unwrap_or_else(|| fail("--std-dev expects a non-negative number"));
+            }
+            "--output" | "-o" => output = Some(value().to_string()),
+            "--corrupt-rate" => {
+                corruption.get_or_insert_with(CorruptionConfig::default).rate = value().parse().ok().filter(|rate| (0.0..=1.0).contains(rate))
+                    .unwrap_or_else(|| fail("--corrupt-rate expects a number between 0 and 1"));
+            }
+            "--corrupt" => {
+                corruption.get_or_insert_with(CorruptionConfig::default).kinds = value().split(',').map(|kind| kind.parse::<Corruption>())
+                    .collect::<Result<_, _>>()
+                    .unwrap_or_else(|e| fail(&e));
+            }
+            "--manifest" => manifest_path = Some(value().to_string()),
+            _ => {
+                print_usage(&args[0]);
+                process::exit(1);
+            }
+        }
+        i += 2;
+    }
+
+    // The manifest sits next to the output unless given explicitly
+    let manifest_path = match (&corruption, manifest_path, &output) {
+        (None, Some(_), _) => fail("--manifest needs --corrupt-rate or --corrupt"),
+        (None, None, _) => None,
+        (Some(_), Some(path), _) => Some(path),
+        (Some(_), None, Some(output)) => Some(format!("{}.manifest", output)),
+        (Some(_), None, None) => fail("--manifest FILE is required when writing corrupted data to stdout"),
+    };
+
+    let mut rng = Rng::new(seed);
+    let stations = match &station_list {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+            parse_station_list(&text, std_deviation).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)))
+        }
+        None => generate_stations(&config, &mut rng).unwrap_or_else(|e| fail(&e)),
+    };
+
+    let mut writer: BufWriter<Box<dyn Write>> = match &output {
+        Some(path) => BufWriter::new(Box::new(File::create(path).unwrap_or_else(|e| fail(&format!("{}: {}", path, e))))),
+        None => BufWriter::new(Box::new(io::stdout().lock())),
+    };
+
+    let written = match &corruption {
+        Some(config) => write_corrupted_measurements(&mut writer, &stations, size, config, &mut rng).map(|(rows, bytes, manifest)| (rows, bytes, Some(manifest))),
+        None => write_measurements(&mut writer, &stations, size, &mut rng).map(|(rows, bytes)| (rows, bytes, None)),
+    };
+    let (rows, bytes, manifest) = written.and_then(|written| writer.flush().map(|()| written)).unwrap_or_else(|e| fail(&e.to_string()));
+    eprintln!("Wrote {} rows ({} bytes) for {} stations", rows, bytes, stations.len());
+
+    if let (Some(manifest), Some(path)) = (manifest, manifest_path) {
+        let mut file = File::create(&path).map(BufWriter::new).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+        manifest.write_to(&mut file).and_then(|()| file.flush()).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+        let errors = manifest.expected_errors().len();
+        eprintln!("Corrupted {} lines ({} should be rejected), manifest in {}", manifest.entries.len(), errors, path);
+    }
+}
@@ -1,14 +1,14 @@
 use std::env;
 use std::process;
-use std::collections::HashSet;
-use obr::WeatherCsvReader;
+use obr::{RecordSource, WeatherInput, WeatherStats};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
     
     if args.len() != 2 {
-        eprintln!("Usage: {} <weather_file.csv>", args[0]);
+        eprintln!("Usage: {} <weather_file.csv | ->", args[0]);
         eprintln!("Example: {} weather_data.csv", args[0]);
+        eprintln!("Example: zcat measurements.txt.gz | {} -", args[0]);
         process::exit(1);
     }
     
@@ -24,36 +24,32 @@ fn main() {
 }
 
 fn read_weather_stats(file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut reader = WeatherCsvReader::from_path(file_path)?;
-    let mut unique_stations: HashSet<String> = HashSet::new();
-    let mut longest_station_name_length = 0;
-    let mut total_records = 0;
-    let mut longest_station_name = String::new();
+    // "-" reads stdin; regular files are memory-mapped, pipes and FIFOs are streamed
+    let mut reader = WeatherInput::open(file_path)?;
+    let station_stats = reader.aggregate()?;
     
-    for record_result in reader.records() {
-        let record = record_result?;
-        total_records += 1;
-        
-        // Track unique stations
-        unique_stations.insert(record.station.clone());
-        
-        // Track longest station name
-        if record.station.len() > longest_station_name_length {
-            longest_station_name_length = record.station.len();
-            longest_station_name = record.station.clone();
-        }
-    }
-    
-    if total_records == 0 {
+    let Some(stats) = WeatherStats::from_station_stats(&station_stats) else {
         eprintln!("No weather records found in the file.");
         return Ok(());
-    }
+    };
+    
+    // Longest name in bytes; ties go to the alphabetically first so the output is stable
+    let longest_station_name = station_stats
+        .keys()
+        .max_by(|a, b| a.len().cmp(&b.len()).then_with(|| b.cmp(a)))
+        .cloned()
+        .unwrap_or_default();
     
     // Output statistics in field: value format
-    println!("TotalStations: {}", unique_stations.len());
-    println!("LongestStationNameLength: {}", longest_station_name_length);
+    println!("TotalStations: {}", stats.unique_stations);
+    println!("LongestStationNameLength: {}", longest_station_name.len());
     println!("LongestStationName: {}", longest_station_name);
-    println!("TotalRecords: {}", total_records);
+    println!("TotalRecords: {}", stats.total_records);
+    println!("MinTemperature: {:.1}", stats.min_temperature);
+    println!("MinTemperatureStation: {}", stats.min_temperature_station);
+    println!("MaxTemperature: {:.1}", stats.max_temperature);
+    println!("MaxTemperatureStation: {}", stats.max_temperature_station);
+    println!("AvgTemperature: {:.1}", stats.avg_temperature);
     
     Ok(())
-}
\ No newline at end of file
+}
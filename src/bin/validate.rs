@@ -0,0 +1,126 @@
+use std::env;
+use std::process;
+use std::time::Instant;
+use obr::{compare_references, find_pipeline, oracle_reference, PipelineEntry, Reference, PIPELINES};
+
+const DEFAULT_TOLERANCE: f64 = 1e-9;
+
+fn print_usage(program: &str) {
+    eprintln!("Usage: {} [--pipeline NAME|all]... [--expected FILE] [--tolerance X] <weather_file>", program);
+    eprintln!("Compares pipeline results with a saved obr output (--expected) or, by default, the in-crate oracle.");
+    eprintln!("Example: {} --pipeline mmap-fused data/measurements_1KRecords.txt", program);
+    eprintln!("Example: {} --pipeline all --expected data/measurements_1KRecords.out data/measurements_1KRecords.txt", program);
+    eprintln!("Pipelines: {}", PIPELINES.iter().map(|pipeline| pipeline.name).collect::<Vec<_>>().join(", "));
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("Error: {}", message);
+    process::exit(1);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut pipelines: Vec<&PipelineEntry> = Vec::new();
+    let mut expected_path: Option<String> = None;
+    let mut tolerance = DEFAULT_TOLERANCE;
+    let mut file_path: Option<String> = None;
+
+    let mut i = 1;
+    while i < args.len() {
+        let value = || match args.get(i + 1) {
+            Some(value) => value.as_str(),
+            None => {
+                print_usage(&args[0]);
+                process::exit(1);
+            }
+        };
+
+        match args[i].as_str() {
+            "--pipeline" => {
+                match value() {
+                    "all" => pipelines.extend(PIPELINES),
+                    name => pipelines.push(find_pipeline(name).unwrap_or_else(|| fail(&format!("Unknown pipeline '{}'", name)))),
+                }
+                i += 2;
+            }
+            "--expected" => {
+                expected_path = Some(value().to_string());
+                i += 2;
+            }
+            "--tolerance" => {
+                tolerance = value().parse().ok().filter(|&x: &f64| x.is_finite() && x >= 0.0)
+                    .unwrap_or_else(|| fail("--tolerance expects a non-negative number"));
+                i += 2;
+            }
+            arg if !arg.starts_with("--") && file_path.is_none() => {
+                file_path = Some(arg.to_string());
+                i += 1;
+            }
+            _ => {
+                print_usage(&args[0]);
+                process::exit(1);
+            }
+        }
+    }
+
+    let Some(file_path) = file_path else {
+        print_usage(&args[0]);
+        process::exit(1);
+    };
+    if pipelines.is_empty() {
+        pipelines.push(find_pipeline("current").expect("the current pipeline is registered"));
+    }
+
+    // The reference is an answer, or the error every pipeline is expected to stop with
+    let (reference, source) = match &expected_path {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+            (Ok(Reference::parse_output(&text).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)))), path.as_str())
+        }
+        None => {
+            let data = std::fs::read(&file_path).unwrap_or_else(|e| fail(&format!("{}: {}", file_path, e)));
+            (oracle_reference(&data), "oracle")
+        }
+    };
+    match &reference {
+        Ok(reference) => println!("Reference: {} ({} stations)", source, reference.stations.len()),
+        Err(e) => println!("Reference: {} rejects the input: {}", source, e),
+    }
+
+    let mut failures = 0;
+    for pipeline in pipelines {
+        let start = Instant::now();
+        let result = (pipeline.run)(&file_path);
+        let elapsed = start.elapsed();
+
+        let problems = match (&reference, result) {
+            (Ok(reference), Ok(station_stats)) => {
+                compare_references(reference, &Reference::from_station_stats(&station_stats), tolerance)
+                    .iter()
+                    .map(|diff| diff.to_string())
+                    .collect()
+            }
+            (Ok(_), Err(e)) => vec![format!("failed: {}", e)],
+            (Err(_), Ok(_)) => vec!["accepted input the reference rejects".to_string()],
+            (Err(expected), Err(actual)) => match (expected.kind(), actual.kind()) {
+                (Some(expected_kind), Some(actual_kind)) if expected_kind == actual_kind => Vec::new(),
+                _ => vec![format!("expected error '{}', got '{}'", expected, actual)],
+            },
+        };
+
+        if problems.is_empty() {
+            println!("{}: OK ({:.2?})", pipeline.name, elapsed);
+        } else {
+            failures += 1;
+            println!("{}: {} difference(s) ({:.2?})", pipeline.name, problems.len(), elapsed);
+            for problem in &problems {
+                println!("  {}", problem);
+            }
+        }
+    }
+
+    if failures > 0 {
+        fail(&format!("{} pipeline(s) disagree with the reference", failures));
+    }
+}
+
@@ -0,0 +1,240 @@
+use crate::raw_table::RawTable;
+use crate::xxhash::{XxHash64, XxHash64Builder, Xxh3_64, Xxh3_64Builder};
+
+const INITIAL_CAPACITY: usize = 16;
+
+// Hashes a raw key in one call, bypassing the `Hash` trait (no length prefix or 0xFF terminator)
+pub trait BytesHasher {
+    fn hash_bytes(&self, bytes: &[u8]) -> u64;
+}
+
+impl BytesHasher for XxHash64Builder {
+    #[inline]
+    fn hash_bytes(&self, bytes: &[u8]) -> u64 {
+        XxHash64::oneshot(bytes, self.seed())
+    }
+}
+
+impl BytesHasher for Xxh3_64Builder {
+    #[inline]
+    fn hash_bytes(&self, bytes: &[u8]) -> u64 {
+        Xxh3_64::oneshot(bytes, self.seed())
+    }
+}
+
+// Open-addressing table keyed by byte strings such as station names. The full hash is
+// stored next to each entry, so growth never re-hashes and most mismatching probes are
+// rejected without comparing key bytes.
+pub struct ByteHashTable<V, S = Xxh3_64Builder> {
+    raw: RawTable<Box<[u8]>, V>,
+    hasher: S,
+}
+
+impl<V> ByteHashTable<V, Xxh3_64Builder> {
+    pub fn new() -> Self {
+        Self::with_capacity(INITIAL_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, Xxh3_64Builder::default())
+    }
+}
+
+impl<V, S: BytesHasher> ByteHashTable<V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(INITIAL_CAPACITY, hasher)
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        Self {
+            raw: RawTable::with_capacity(capacity),
+            hasher,
+        }
+    }
+
+    #[inline]
+    pub fn hash_key(&self, key: &[u8]) -> u64 {
+        self.hasher.hash_bytes(key)
+    }
+
+    #[inline]
+    fn find_index(&self, hash: u64, key: &[u8]) -> Option<usize> {
+        self.raw.find(hash, |candidate| **candidate == *key)
+    }
+
+    pub fn insert(&mut self, key: &[u8], value: V) -> Option<V> {
+        self.insert_with_hash(self.hash_key(key), key, value)
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&V> {
+        let index = self.find_index(self.hash_key(key), key)?;
+        Some(&self.raw.bucket(index).value)
+    }
+
+    pub fn get_mut(&mut self, key: &[u8]) -> Option<&mut V> {
+        self.get_mut_with_hash(self.hash_key(key), key)
+    }
+
+    // Copies `key` into the table only when it is not already present
+    #[inline]
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: &[u8], default: F) -> &mut V {
+        let hash = self.hash_key(key);
+        let index = match self.find_index(hash, key) {
+            Some(index) => index,
+            None => {
+                self.raw.reserve_one();
+                self.raw.insert_new(hash, key.into(), default())
+            }
+        };
+        &mut self.raw.bucket_mut(index).value
+    }
+
+    // The `_with_hash` variants take a hash the caller already computed; it must equal
+    // `hash_key(key)` for this table's hasher
+    #[inline]
+    pub fn get_mut_with_hash(&mut self, hash: u64, key: &[u8]) -> Option<&mut V> {
+        let index = self.find_index(hash, key)?;
+        Some(&mut self.raw.bucket_mut(index).value)
+    }
+
+    pub fn insert_with_hash(&mut self, hash: u64, key: &[u8], value: V) -> Option<V> {
+        debug_assert_eq!(hash, self.hash_key(key));
+        if let Some(index) = self.find_index(hash, key) {
+            return Some(std::mem::replace(&mut self.raw.bucket_mut(index).value, value));
+        }
+
+        self.raw.reserve_one();
+        self.raw.insert_new(hash, key.into(), value);
+        None
+    }
+
+    pub fn remove(&mut self, key: &[u8]) -> Option<V> {
+        let index = self.find_index(self.hash_key(key), key)?;
+        Some(self.raw.remove(index).value)
+    }
+
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw.len() == 0
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &[u8]> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &V)> {
+        self.raw.iter().map(|bucket| (&*bucket.key, &bucket.value))
+    }
+}
+
+// Moves every key and value out of the table, in slot order
+pub struct ByteIntoIter<V> {
+    buckets: <RawTable<Box<[u8]>, V> as IntoIterator>::IntoIter,
+}
+
+impl<V> Iterator for ByteIntoIter<V> {
+    type Item = (Box<[u8]>, V);
+
+    fn next(&mut self) -> Option<(Box<[u8]>, V)> {
+        self.buckets.next().map(|bucket| (bucket.key, bucket.value))
+    }
+}
+
+impl<V, S> IntoIterator for ByteHashTable<V, S> {
+    type Item = (Box<[u8]>, V);
+    type IntoIter = ByteIntoIter<V>;
+
+    fn into_iter(self) -> ByteIntoIter<V> {
+        ByteIntoIter { buckets: self.raw.into_iter() }
+    }
+}
+
+impl<V, S: BytesHasher + Default> Default for ByteHashTable<V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_get() {
+        let mut table = ByteHashTable::new();
+        assert_eq!(table.insert(b"Hamburg", 12), None);
+        assert_eq!(table.insert(b"Abha", 18), None);
+        assert_eq!(table.insert(b"Hamburg", 13), Some(12));
+
+        assert_eq!(table.get(b"Hamburg"), Some(&13));
+        assert_eq!(table.get(b"Abha"), Some(&18));
+        assert_eq!(table.get(b"Bulawayo"), None);
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn test_hashes_raw_bytes() {
+        let table: ByteHashTable<()> = ByteHashTable::new();
+        assert_eq!(table.hash_key(b"Hamburg"), Xxh3_64::oneshot(b"Hamburg", 0));
+
+        let table: ByteHashTable<(), XxHash64Builder> = ByteHashTable::with_hasher(XxHash64Builder::new(5));
+        assert_eq!(table.hash_key(b"Hamburg"), XxHash64::oneshot(b"Hamburg", 5));
+    }
+
+    #[test]
+    fn test_get_or_insert_with() {
+        let mut table = ByteHashTable::with_capacity(1);
+        let line: &[u8] = b"Station1;1.0";
+
+        for i in 0..50 {
+            *table.get_or_insert_with(&line[..8], || 0) += 1;
+            *table.get_or_insert_with(format!("Other{}", i % 5).as_bytes(), || 0) += 1;
+        }
+
+        assert_eq!(table.get(b"Station1"), Some(&50));
+        assert_eq!(table.get(b"Other0"), Some(&10));
+        assert_eq!(table.len(), 6);
+    }
+
+    #[test]
+    fn test_remove_and_grow() {
+        let mut table: ByteHashTable<u32, XxHash64Builder> = ByteHashTable::default();
+
+        for i in 0..300u32 {
+            table.insert(&i.to_le_bytes(), i);
+        }
+        for i in (0..300u32).filter(|i| i % 2 == 0) {
+            assert_eq!(table.remove(&i.to_le_bytes()), Some(i));
+        }
+
+        assert_eq!(table.len(), 150);
+        for i in 0..300u32 {
+            let expected = if i % 2 == 0 { None } else { Some(&i) };
+            assert_eq!(table.get(&i.to_le_bytes()), expected);
+        }
+        assert_eq!(table.iter().count(), 150);
+        assert!(table.keys().all(|key| table.contains_key(key)));
+    }
+
+    #[test]
+    fn test_into_iter_moves_entries() {
+        let mut table = ByteHashTable::new();
+        table.insert(b"Hamburg", 12);
+        table.insert(b"Abha", 18);
+
+        let mut entries: Vec<(Box<[u8]>, i32)> = table.into_iter().collect();
+        entries.sort();
+        assert_eq!(entries, vec![(b"Abha"[..].into(), 18), (b"Hamburg"[..].into(), 12)]);
+    }
+}
@@ -0,0 +1,346 @@
+use std::io::{self, Write};
+use crate::generator::{write_rows, OutputSize, Rng, StationSpec};
+use crate::ErrorKind;
+
+// Damage the corruption writer can apply. Per-row kinds hit rows at the configured rate;
+// `Bom` (start of the file) and `TruncatedLastLine` (end of the file) are applied once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Corruption {
+    MissingDelimiter,
+    ExtraColumns,
+    EmptyStation,
+    NonNumericTemperature,
+    InvalidUtf8,
+    Crlf,
+    Bom,
+    TruncatedLastLine,
+}
+
+impl Corruption {
+    pub const ALL: [Corruption; 8] = [
+        Corruption::MissingDelimiter,
+        Corruption::ExtraColumns,
+        Corruption::EmptyStation,
+        Corruption::NonNumericTemperature,
+        Corruption::InvalidUtf8,
+        Corruption::Crlf,
+        Corruption::Bom,
+        Corruption::TruncatedLastLine,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Corruption::MissingDelimiter => "missing-delimiter",
+            Corruption::ExtraColumns => "extra-columns",
+            Corruption::EmptyStation => "empty-station",
+            Corruption::NonNumericTemperature => "non-numeric-temperature",
+            Corruption::InvalidUtf8 => "invalid-utf8",
+            Corruption::Crlf => "crlf",
+            Corruption::Bom => "bom",
+            Corruption::TruncatedLastLine => "truncated-last-line",
+        }
+    }
+
+    // The error every reader must report for the corrupted line; CRLF line endings and a
+    // leading byte order mark are accepted, so the line still has to parse
+    pub fn expected_error(&self) -> Option<ErrorKind> {
+        match self {
+            Corruption::MissingDelimiter | Corruption::TruncatedLastLine => Some(ErrorKind::MissingDelimiter),
+            Corruption::ExtraColumns => Some(ErrorKind::TooManyColumns),
+            Corruption::EmptyStation => Some(ErrorKind::EmptyStation),
+            Corruption::NonNumericTemperature => Some(ErrorKind::BadNumber),
+            Corruption::InvalidUtf8 => Some(ErrorKind::InvalidUtf8),
+            Corruption::Crlf | Corruption::Bom => None,
+        }
+    }
+
+    fn is_per_row(&self) -> bool {
+        !matches!(self, Corruption::Bom | Corruption::TruncatedLastLine)
+    }
+}
+
+impl std::fmt::Display for Corruption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Corruption {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Corruption::ALL
+            .into_iter()
+            .find(|corruption| corruption.name() == s.trim())
+            .ok_or_else(|| format!("Unknown corruption '{}' (expected one of: {})", s, Corruption::ALL.map(|c| c.name()).join(", ")))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorruptionConfig {
+    // Probability that a row gets one of the per-row kinds
+    pub rate: f64,
+    pub kinds: Vec<Corruption>,
+}
+
+impl Default for CorruptionConfig {
+    fn default() -> Self {
+        Self { rate: 0.01, kinds: Corruption::ALL.to_vec() }
+    }
+}
+
+// One corrupted line; `line` is 1-based, counted the way the readers count lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub line: usize,
+    pub corruption: Corruption,
+}
+
+// Sidecar listing every corrupted line, written as `line;corruption;expected error`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    // (line, kind) of each line a lenient reader must skip, in line order
+    pub fn expected_errors(&self) -> Vec<(usize, ErrorKind)> {
+        let mut errors: Vec<(usize, ErrorKind)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| entry.corruption.expected_error().map(|kind| (entry.line, kind)))
+            .collect();
+        errors.sort_by_key(|&(line, _)| line);
+        errors
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "# line;corruption;expected error")?;
+        for entry in &self.entries {
+            let expected = entry.corruption.expected_error().map_or("none".to_string(), |kind| kind.to_string());
+            writeln!(writer, "{};{};{}", entry.line, entry.corruption, expected)?;
+        }
+        Ok(())
+    }
+
+    // Reads back what write_to produced; the expected error column is derived, so it's ignored
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut entries = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split(';');
+            let number = fields.next().and_then(|field| field.trim().parse::<usize>().ok());
+            let corruption = fields.next().and_then(|field| field.parse::<Corruption>().ok());
+            match (number, corruption) {
+                (Some(line), Some(corruption)) => entries.push(ManifestEntry { line, corruption }),
+                _ => return Err(format!("Line {}: expected 'line;corruption[;expected error]', got '{}'", i + 1, line)),
+            }
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const BAD_TEMPERATURES: &[&[u8]] = &[b"", b"abc", b"N/A", b"1.2.3", b"12,5", b"--4.0", b"NaN"];
+// A lone continuation byte, a lead byte without its continuation, and a byte never valid in UTF-8
+const BAD_UTF8_BYTES: &[u8] = &[0x80, 0xC3, 0xFF];
+
+// write_measurements with corruption injected according to `config`. Returns (rows, bytes)
+// written, counting a truncated last line as a row, and the manifest of corrupted lines.
+pub fn write_corrupted_measurements<W: Write>(
+    writer: &mut W,
+    stations: &[StationSpec],
+    size: OutputSize,
+    config: &CorruptionConfig,
+    rng: &mut Rng,
+) -> io::Result<(u64, u64, Manifest)> {
+    let per_row: Vec<Corruption> = config.kinds.iter().copied().filter(Corruption::is_per_row).collect();
+    let bom = config.kinds.contains(&Corruption::Bom);
+    let mut manifest = Manifest::default();
+
+    let (mut rows, mut bytes) = write_rows(writer, stations, size, rng, |row, line, rng| {
+        let line_number = row as usize + 1;
+        if row == 0 && bom {
+            line.splice(0..0, UTF8_BOM.iter().copied());
+            manifest.entries.push(ManifestEntry { line: line_number, corruption: Corruption::Bom });
+        }
+        if !per_row.is_empty() && config.rate > 0.0 && rng.next_f64() < config.rate {
+            let corruption = per_row[rng.below(per_row.len() as u64) as usize];
+            corrupt_row(line, corruption, row == 0 && bom, rng);
+            manifest.entries.push(ManifestEntry { line: line_number, corruption });
+        }
+    })?;
+
+    if config.kinds.contains(&Corruption::TruncatedLastLine) {
+        // A prefix of a station name with no delimiter and no '\n', as if the write was cut short
+        let name = &stations[rng.below(stations.len() as u64) as usize].name;
+        let chars = name.chars().count();
+        let keep = 1 + rng.below(chars as u64) as usize;
+        let end = name.char_indices().nth(keep).map_or(name.len(), |(index, _)| index);
+
+        writer.write_all(&name.as_bytes()[..end])?;
+        rows += 1;
+        bytes += end as u64;
+        manifest.entries.push(ManifestEntry { line: rows as usize, corruption: Corruption::TruncatedLastLine });
+    }
+
+    Ok((rows, bytes, manifest))
+}
+
+// Rewrites a well-formed `station;temperature\n` row so it has exactly one defect
+fn corrupt_row(line: &mut Vec<u8>, corruption: Corruption, has_bom: bool, rng: &mut Rng) {
+    let station_start = if has_bom { UTF8_BOM.len() } else { 0 };
+    let semicolon = memchr::memchr(b';', line).expect("generated rows have a delimiter");
+    let end = line.len() - 1;
+
+    match corruption {
+        Corruption::MissingDelimiter => line[semicolon] = b' ',
+        Corruption::ExtraColumns => {
+            let temperature = line[semicolon..end].to_vec();
+            line.splice(end..end, temperature);
+        }
+        Corruption::EmptyStation => {
+            let padding: &[u8] = if rng.below(2) == 0 { b"" } else { b"  " };
+            line.splice(station_start..semicolon, padding.iter().copied());
+        }
+        Corruption::NonNumericTemperature => {
+            let temperature = BAD_TEMPERATURES[rng.below(BAD_TEMPERATURES.len() as u64) as usize];
+            line.splice(semicolon + 1..end, temperature.iter().copied());
+        }
+        Corruption::InvalidUtf8 => {
+            // Insert on a character boundary so the only invalid sequence is the inserted byte
+            let station = std::str::from_utf8(&line[station_start..semicolon]).expect("generated station names are UTF-8");
+            let boundaries: Vec<usize> = station.char_indices().map(|(index, _)| index).chain([station.len()]).collect();
+            let at = station_start + boundaries[rng.below(boundaries.len() as u64) as usize];
+            line.insert(at, BAD_UTF8_BYTES[rng.below(BAD_UTF8_BYTES.len() as u64) as usize]);
+        }
+        Corruption::Crlf => line.insert(end, b'\r'),
+        Corruption::Bom | Corruption::TruncatedLastLine => unreachable!("{} is not a per-row corruption", corruption),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use std::io::Cursor;
+
+    fn corrupted(seed: u64, rows: u64, config: &CorruptionConfig) -> (Vec<u8>, Manifest) {
+        let mut rng = Rng::new(seed);
+        let station_config = StationConfig { count: 100, ..StationConfig::default() };
+        let stations = generate_stations(&station_config, &mut rng).unwrap();
+        let mut output = Vec::new();
+        let (_, bytes, manifest) = write_corrupted_measurements(&mut output, &stations, OutputSize::Rows(rows), config, &mut rng).unwrap();
+        assert_eq!(bytes, output.len() as u64);
+        (output, manifest)
+    }
+
+    fn skipped(report: &ErrorReport) -> Vec<(usize, ErrorKind)> {
+        report.skipped().iter().map(|line| (line.line_number, line.kind)).collect()
+    }
+
+    #[test]
+    fn test_readers_report_exactly_the_manifest_lines() {
+        let config = CorruptionConfig { rate: 0.05, ..CorruptionConfig::default() };
+        let (data, manifest) = corrupted(11, 20_000, &config);
+        let expected = manifest.expected_errors();
+        let good_rows = 20_001 - expected.len();
+
+        // Every kind shows up, and line 1 starts with the byte order mark
+        for corruption in Corruption::ALL {
+            assert!(manifest.entries.iter().any(|entry| entry.corruption == corruption), "{} never injected", corruption);
+        }
+        assert!(data.starts_with(UTF8_BOM));
+        assert!(!data.ends_with(b"\n"));
+
+        let lenient = ErrorPolicy::lenient();
+        let mut reader = WeatherCsvReader::from_reader(Cursor::new(&data[..])).with_error_policy(lenient);
+        assert_eq!(reader.collect_records().unwrap().len(), good_rows);
+        assert_eq!(skipped(reader.error_report()), expected, "WeatherCsvReader");
+
+        let mut source = SliceRecordSource::new(&data).with_error_policy(lenient);
+        let stations = source.aggregate().unwrap();
+        assert!(stations.keys().all(|station| !station.starts_with('\u{FEFF}')));
+        assert_eq!(skipped(RecordSource::error_report(&source)), expected, "SliceRecordSource");
+
+        let mut scanner = FusedScanner::new(&data).with_error_policy(lenient);
+        assert_eq!(scanner.collect_records().unwrap().len(), good_rows);
+        assert_eq!(skipped(scanner.error_report()), expected, "FusedScanner");
+
+        let mut input = WeatherInput::from_reader(Cursor::new(data.clone())).with_error_policy(lenient);
+        assert_eq!(input.collect_records().unwrap().len(), good_rows);
+        assert_eq!(skipped(&input.into_error_report()), expected, "WeatherInput");
+
+        let path = std::env::temp_dir().join(format!("obr_corruption_{}.txt", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+        let mut mapped = MmapWeatherCsvReader::from_path(&path).unwrap().with_error_policy(lenient);
+        assert_eq!(mapped.collect_records().unwrap().len(), good_rows);
+        assert_eq!(skipped(mapped.error_report()), expected, "MmapWeatherCsvReader");
+        std::fs::remove_file(&path).unwrap();
+
+        let (records, report) = parse_records_string_with_policy(&data, lenient).unwrap();
+        assert_eq!((records.len(), skipped(&report)), (good_rows, expected.clone()), "parse_records_string_with_policy");
+        let (records, report) = parse_records_bytes_with_policy(&data, lenient).unwrap();
+        assert_eq!((records.len(), skipped(&report)), (good_rows, expected.clone()), "parse_records_bytes_with_policy");
+        let (records, report) = parse_records_bytes_ref_with_policy(&data, lenient).unwrap();
+        assert_eq!((records.len(), skipped(&report)), (good_rows, expected.clone()), "parse_records_bytes_ref_with_policy");
+        let (records, report) = parse_records_fixed_with_policy(&data, lenient).unwrap();
+        assert_eq!((records.len(), skipped(&report)), (good_rows, expected.clone()), "parse_records_fixed_with_policy");
+
+        // Strict readers stop at the first corrupted line
+        let error = SliceRecordSource::new(&data).collect_records().unwrap_err();
+        assert_eq!((error.line(), error.kind()), (Some(expected[0].0), Some(expected[0].1)));
+    }
+
+    #[test]
+    fn test_tolerated_corruption_parses_cleanly() {
+        let config = CorruptionConfig { rate: 0.5, kinds: vec![Corruption::Crlf, Corruption::Bom] };
+        let (data, manifest) = corrupted(5, 1_000, &config);
+        assert!(manifest.expected_errors().is_empty());
+        assert!(manifest.entries.len() > 400);
+
+        let clean: Vec<u8> = data[UTF8_BOM.len()..].iter().copied().filter(|&byte| byte != b'\r').collect();
+        let records = SliceRecordSource::new(&data).collect_records().unwrap();
+        assert_eq!(records, SliceRecordSource::new(&clean).collect_records().unwrap());
+    }
+
+    #[test]
+    fn test_zero_rate_matches_clean_output() {
+        let config = CorruptionConfig { rate: 0.0, ..CorruptionConfig::default() };
+        let (data, manifest) = corrupted(3, 500, &CorruptionConfig { kinds: vec![Corruption::MissingDelimiter], ..config });
+        assert!(manifest.entries.is_empty());
+
+        let mut rng = Rng::new(3);
+        let stations = generate_stations(&StationConfig { count: 100, ..StationConfig::default() }, &mut rng).unwrap();
+        let mut clean = Vec::new();
+        write_measurements(&mut clean, &stations, OutputSize::Rows(500), &mut rng).unwrap();
+        assert_eq!(data, clean);
+    }
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let (_, manifest) = corrupted(9, 2_000, &CorruptionConfig { rate: 0.1, ..CorruptionConfig::default() });
+        let mut text = Vec::new();
+        manifest.write_to(&mut text).unwrap();
+
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.contains(";crlf;none\n"));
+        assert!(text.contains(";missing-delimiter;missing delimiter\n"));
+        assert_eq!(Manifest::parse(&text).unwrap(), manifest);
+        assert!(Manifest::parse("12;sideways\n").is_err());
+    }
+
+    #[test]
+    fn test_corruption_from_str() {
+        for corruption in Corruption::ALL {
+            assert_eq!(corruption.name().parse::<Corruption>().unwrap(), corruption);
+        }
+        assert!("bad-number".parse::<Corruption>().is_err());
+    }
+}
@@ -0,0 +1,369 @@
+use std::io::{self, Write};
+
+// Letters used for generated station names; a few multi-byte ones keep the UTF-8 paths honest
+const NAME_ALPHABET: &[char] = &[
+    'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w',
+    'x', 'y', 'z', 'á', 'é', 'í', 'ó', 'ö', 'ü', 'ñ', 'ç', 'ø', 'å', 'ł', 'ş',
+];
+
+// 1BRC caps station names at 100 bytes
+pub const MAX_STATION_NAME_BYTES: usize = 100;
+
+// xoshiro256** seeded through SplitMix64. Only integer and basic float arithmetic is used
+// downstream, so a seed produces the same bytes on every platform.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut splitmix = seed;
+        let mut next = || {
+            splitmix = splitmix.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = splitmix;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+        Self { state: [next(), next(), next(), next()] }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+        result
+    }
+
+    // Uniform in [0, 1) with 53 bits of precision
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Uniform in [0, bound)
+    pub fn below(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    pub fn range_f64(&mut self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.next_f64()
+    }
+
+    // Approximately standard normal (Irwin–Hall with 12 terms), avoiding ln/cos so the
+    // result doesn't depend on the platform's libm
+    pub fn next_gaussian(&mut self) -> f64 {
+        (0..12).map(|_| self.next_f64()).sum::<f64>() - 6.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StationSpec {
+    pub name: String,
+    pub mean: f64,
+    pub std_deviation: f64,
+}
+
+impl StationSpec {
+    pub fn new(name: String, mean: f64, std_deviation: f64) -> Self {
+        Self { name, mean, std_deviation }
+    }
+}
+
+// Station name length in characters: a fixed value or uniform over an inclusive range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameLength {
+    Fixed(usize),
+    Uniform { min: usize, max: usize },
+}
+
+impl Default for NameLength {
+    fn default() -> Self {
+        NameLength::Uniform { min: 3, max: 24 }
+    }
+}
+
+impl std::str::FromStr for NameLength {
+    type Err = String;
+
+    // "12" or "3-24"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |value: &str| value.trim().parse::<usize>().ok().filter(|n| (1..=MAX_STATION_NAME_BYTES / 2).contains(n));
+        let length = match s.split_once('-') {
+            Some((min, max)) => match (parse(min), parse(max)) {
+                (Some(min), Some(max)) if min <= max => Some(NameLength::Uniform { min, max }),
+                _ => None,
+            },
+            None => parse(s).map(NameLength::Fixed),
+        };
+        length.ok_or_else(|| format!("Invalid name length '{}' (expected N or MIN-MAX, between 1 and {})", s, MAX_STATION_NAME_BYTES / 2))
+    }
+}
+
+impl NameLength {
+    // How many distinct names the alphabet allows at these lengths (saturating)
+    pub fn distinct_names(&self) -> u64 {
+        let (min, max) = match *self {
+            NameLength::Fixed(length) => (length, length),
+            NameLength::Uniform { min, max } => (min, max),
+        };
+        (min..=max)
+            .map(|length| (NAME_ALPHABET.len() as u64).saturating_pow(length as u32))
+            .fold(0, u64::saturating_add)
+    }
+
+    fn sample(&self, rng: &mut Rng) -> usize {
+        match *self {
+            NameLength::Fixed(length) => length,
+            NameLength::Uniform { min, max } => min + rng.below((max - min + 1) as u64) as usize,
+        }
+    }
+}
+
+// Settings for generated stations (used when no station list is given)
+#[derive(Debug, Clone, PartialEq)]
+pub struct StationConfig {
+    pub count: usize,
+    pub name_length: NameLength,
+    // Per-station means are drawn uniformly from this range
+    pub mean_range: (f64, f64),
+    // Per-station standard deviations are drawn uniformly from this range
+    pub std_deviation_range: (f64, f64),
+}
+
+impl Default for StationConfig {
+    fn default() -> Self {
+        Self {
+            count: 400,
+            name_length: NameLength::default(),
+            mean_range: (-20.0, 35.0),
+            std_deviation_range: (3.0, 12.0),
+        }
+    }
+}
+
+// How much to write: a number of rows, or rows until at least this many bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputSize {
+    Rows(u64),
+    Bytes(u64),
+}
+
+// Generates `config.count` stations with unique names
+pub fn generate_stations(config: &StationConfig, rng: &mut Rng) -> Result<Vec<StationSpec>, String> {
+    if config.count as u64 > config.name_length.distinct_names() {
+        return Err(format!("Cannot make {} unique station names with name length {:?}", config.count, config.name_length));
+    }
+
+    let mut names = std::collections::HashSet::with_capacity(config.count);
+    let mut stations = Vec::with_capacity(config.count);
+
+    while stations.len() < config.count {
+        let length = config.name_length.sample(rng);
+        let mut name = String::with_capacity(length * 2);
+        for i in 0..length {
+            let c = NAME_ALPHABET[rng.below(NAME_ALPHABET.len() as u64) as usize];
+            if i == 0 {
+                name.extend(c.to_uppercase());
+            } else {
+                name.push(c);
+            }
+        }
+        if !names.insert(name.clone()) {
+            continue;
+        }
+
+        let mean = round_to_tenth(rng.range_f64(config.mean_range.0, config.mean_range.1));
+        let std_deviation = round_to_tenth(rng.range_f64(config.std_deviation_range.0, config.std_deviation_range.1));
+        stations.push(StationSpec::new(name, mean, std_deviation));
+    }
+
+    Ok(stations)
+}
+
+// Parses a station list with one `name;mean[;std_deviation]` per line ('#' starts a comment)
+pub fn parse_station_list(text: &str, default_std_deviation: f64) -> Result<Vec<StationSpec>, String> {
+    let mut stations = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(';').map(str::trim).collect();
+        let number = |field: &str| field.parse::<f64>().ok().filter(|value| value.is_finite());
+        let spec = match fields.as_slice() {
+            [name, mean] => number(mean).map(|mean| (name, mean, default_std_deviation)),
+            [name, mean, std_deviation] => number(mean).zip(number(std_deviation)).map(|(mean, std)| (name, mean, std)),
+            _ => None,
+        };
+        match spec {
+            Some((name, mean, std_deviation)) if !name.is_empty() && name.len() <= MAX_STATION_NAME_BYTES => {
+                stations.push(StationSpec::new(name.to_string(), mean, std_deviation));
+            }
+            _ => return Err(format!("Line {}: expected 'name;mean[;std_deviation]', got '{}'", i + 1, line)),
+        }
+    }
+
+    if stations.is_empty() {
+        return Err("Station list is empty".to_string());
+    }
+    Ok(stations)
+}
+
+// Writes measurements for randomly chosen stations; returns (rows, bytes) written
+pub fn write_measurements<W: Write>(writer: &mut W, stations: &[StationSpec], size: OutputSize, rng: &mut Rng) -> io::Result<(u64, u64)> {
+    write_rows(writer, stations, size, rng, |_, _, _| {})
+}
+
+// Row writer behind write_measurements; `edit` gets the 0-based row index and may rewrite the
+// row (including its '\n') before it is written
+pub(crate) fn write_rows<W, F>(writer: &mut W, stations: &[StationSpec], size: OutputSize, rng: &mut Rng, mut edit: F) -> io::Result<(u64, u64)>
+where
+    W: Write,
+    F: FnMut(u64, &mut Vec<u8>, &mut Rng),
+{
+    assert!(!stations.is_empty(), "At least one station is required");
+
+    let mut rows = 0u64;
+    let mut bytes = 0u64;
+    let mut line = Vec::with_capacity(MAX_STATION_NAME_BYTES + 8);
+
+    loop {
+        let done = match size {
+            OutputSize::Rows(limit) => rows >= limit,
+            OutputSize::Bytes(limit) => bytes >= limit,
+        };
+        if done {
+            break;
+        }
+
+        let station = &stations[rng.below(stations.len() as u64) as usize];
+        let temperature = station.mean + station.std_deviation * rng.next_gaussian();
+
+        line.clear();
+        line.extend_from_slice(station.name.as_bytes());
+        line.push(b';');
+        push_temperature(&mut line, temperature);
+        line.push(b'\n');
+        edit(rows, &mut line, rng);
+
+        writer.write_all(&line)?;
+        rows += 1;
+        bytes += line.len() as u64;
+    }
+
+    Ok((rows, bytes))
+}
+
+fn round_to_tenth(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+// Appends the temperature clamped to -99.9..=99.9 with exactly one decimal, formatted from
+// integer tenths so no float formatting is involved
+pub(crate) fn push_temperature(line: &mut Vec<u8>, temperature: f64) {
+    let tenths = ((temperature * 10.0).round() as i64).clamp(-999, 999);
+    if tenths < 0 {
+        line.push(b'-');
+    }
+    let abs = tenths.unsigned_abs();
+    line.extend_from_slice((abs / 10).to_string().as_bytes());
+    line.push(b'.');
+    line.push(b'0' + (abs % 10) as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HashTable, RecordSource, SliceRecordSource, StationVarianceStats, XxHash64};
+
+    fn generate(seed: u64, config: &StationConfig, size: OutputSize) -> Vec<u8> {
+        let mut rng = Rng::new(seed);
+        let stations = generate_stations(config, &mut rng).unwrap();
+        let mut output = Vec::new();
+        write_measurements(&mut output, &stations, size, &mut rng).unwrap();
+        output
+    }
+
+    #[test]
+    fn test_output_is_reproducible() {
+        let config = StationConfig::default();
+        let first = generate(42, &config, OutputSize::Rows(10_000));
+        assert_eq!(first, generate(42, &config, OutputSize::Rows(10_000)));
+        assert_ne!(first, generate(43, &config, OutputSize::Rows(10_000)));
+
+        // Pin the exact bytes so changes to the generator are deliberate
+        assert_eq!(XxHash64::oneshot(&first, 0), 0xF760_B14E_F7DC_F59F);
+    }
+
+    #[test]
+    fn test_output_parses_and_respects_options() {
+        let config = StationConfig { count: 50, name_length: NameLength::Uniform { min: 2, max: 6 }, ..StationConfig::default() };
+        let output = generate(7, &config, OutputSize::Rows(5_000));
+
+        let stats = SliceRecordSource::new(&output).aggregate().unwrap();
+        assert!(stats.len() <= 50);
+        assert_eq!(stats.values().map(|stats| stats.count).sum::<usize>(), 5_000);
+        for (station, stats) in stats.iter() {
+            assert!((2..=6).contains(&station.chars().count()), "{}", station);
+            assert!(stats.min_temperature >= -99.9 && stats.max_temperature <= 99.9);
+        }
+    }
+
+    #[test]
+    fn test_output_size_in_bytes() {
+        let config = StationConfig { count: 10, ..StationConfig::default() };
+        let output = generate(1, &config, OutputSize::Bytes(64 * 1024));
+        assert!(output.len() >= 64 * 1024);
+        assert!(output.len() < 64 * 1024 + MAX_STATION_NAME_BYTES + 8);
+        assert_eq!(output.last(), Some(&b'\n'));
+    }
+
+    #[test]
+    fn test_station_means_follow_spec() {
+        let stations = vec![StationSpec::new("Hot".to_string(), 30.0, 2.0), StationSpec::new("Cold".to_string(), -30.0, 2.0)];
+        let mut output = Vec::new();
+        write_measurements(&mut output, &stations, OutputSize::Rows(20_000), &mut Rng::new(3)).unwrap();
+
+        let stats: HashTable<String, StationVarianceStats> = SliceRecordSource::new(&output).aggregate_with().unwrap();
+        assert!((stats.get("Hot").unwrap().stats.avg_temperature() - 30.0).abs() < 0.1);
+        assert!((stats.get("Cold").unwrap().stats.avg_temperature() + 30.0).abs() < 0.1);
+        assert!((stats.get("Hot").unwrap().std_deviation() - 2.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_parse_station_list() {
+        let stations = parse_station_list("# name;mean;std\nHamburg;9.7\nBulawayo;18.9;4.5\n\n", 10.0).unwrap();
+        assert_eq!(stations, vec![
+            StationSpec::new("Hamburg".to_string(), 9.7, 10.0),
+            StationSpec::new("Bulawayo".to_string(), 18.9, 4.5),
+        ]);
+        assert!(parse_station_list("Hamburg", 10.0).is_err());
+        assert!(parse_station_list("Hamburg;warm", 10.0).is_err());
+        assert!(parse_station_list("", 10.0).is_err());
+    }
+
+    #[test]
+    fn test_too_many_stations_for_name_length() {
+        let config = StationConfig { count: 39, name_length: NameLength::Fixed(1), ..StationConfig::default() };
+        assert!(generate_stations(&config, &mut Rng::new(1)).is_err());
+
+        let config = StationConfig { count: 38, ..config };
+        assert_eq!(generate_stations(&config, &mut Rng::new(1)).unwrap().len(), 38);
+    }
+
+    #[test]
+    fn test_name_length_from_str() {
+        assert_eq!("12".parse(), Ok(NameLength::Fixed(12)));
+        assert_eq!("3-24".parse(), Ok(NameLength::Uniform { min: 3, max: 24 }));
+        assert!("24-3".parse::<NameLength>().is_err());
+        assert!("0".parse::<NameLength>().is_err());
+    }
+}
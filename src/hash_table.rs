@@ -1,16 +1,18 @@
-use std::hash::Hash;
-use crate::xxhash::XxHash64;
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash, Hasher};
+use crate::raw_table::RawTable;
+use crate::xxhash::XxHash64Builder;
 
 const INITIAL_CAPACITY: usize = 16;
-const LOAD_FACTOR_THRESHOLD: f64 = 0.75;
 
-pub struct HashTable<K, V> {
-    buckets: Vec<Vec<(K, V)>>,
-    size: usize,
-    capacity: usize,
+// Open-addressing table with linear probing over a power-of-two number of slots.
+// Removal uses backward-shift deletion, so no tombstones are ever left behind.
+pub struct HashTable<K, V, S = XxHash64Builder> {
+    raw: RawTable<K, V>,
+    hash_builder: S,
 }
 
-impl<K, V> HashTable<K, V>
+impl<K, V> HashTable<K, V, XxHash64Builder>
 where
     K: Hash + Eq + Clone,
     V: Clone,
@@ -20,124 +22,292 @@ where
     }
 
     pub fn with_capacity(capacity: usize) -> Self {
-        let mut buckets = Vec::with_capacity(capacity);
-        for _ in 0..capacity {
-            buckets.push(Vec::new());
-        }
-        
+        Self::with_capacity_and_hasher(capacity, XxHash64Builder::default())
+    }
+}
+
+impl<K, V, S> HashTable<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher,
+{
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_capacity_and_hasher(INITIAL_CAPACITY, hash_builder)
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
         Self {
-            buckets,
-            size: 0,
-            capacity,
+            raw: RawTable::with_capacity(capacity),
+            hash_builder,
         }
     }
 
-    fn hash(&self, key: &K) -> usize {
-        let mut hasher = XxHash64::new(0);
-        key.hash(&mut hasher);
-        (hasher.finish() as usize) % self.capacity
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
     }
 
-    fn load_factor(&self) -> f64 {
-        self.size as f64 / self.capacity as f64
+    fn hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        self.hash_builder.hash_one(key)
     }
 
-    fn resize(&mut self) {
-        let old_buckets = std::mem::take(&mut self.buckets);
-        self.capacity *= 2;
-        self.size = 0;
-        
-        self.buckets = Vec::with_capacity(self.capacity);
-        for _ in 0..self.capacity {
-            self.buckets.push(Vec::new());
-        }
+    #[cfg(test)]
+    fn capacity(&self) -> usize {
+        self.raw.capacity()
+    }
 
-        for bucket in old_buckets {
-            for (key, value) in bucket {
-                self.insert(key, value);
-            }
-        }
+    fn find_index<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.raw.find(hash, |candidate| candidate.borrow() == key)
     }
 
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        if self.load_factor() > LOAD_FACTOR_THRESHOLD {
-            self.resize();
+        let hash = self.hash(&key);
+        if let Some(index) = self.find_index(hash, &key) {
+            return Some(std::mem::replace(&mut self.raw.bucket_mut(index).value, value));
         }
 
-        let index = self.hash(&key);
-        let bucket = &mut self.buckets[index];
-
-        for (existing_key, existing_value) in bucket.iter_mut() {
-            if existing_key == &key {
-                return Some(std::mem::replace(existing_value, value));
-            }
-        }
-
-        bucket.push((key, value));
-        self.size += 1;
+        self.raw.reserve_one();
+        self.raw.insert_new(hash, key, value);
         None
     }
 
-    pub fn get(&self, key: &K) -> Option<&V> {
-        let index = self.hash(key);
-        let bucket = &self.buckets[index];
-
-        for (existing_key, existing_value) in bucket {
-            if existing_key == key {
-                return Some(existing_value);
-            }
-        }
-
-        None
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find_index(self.hash(key), key)?;
+        Some(&self.raw.bucket(index).value)
     }
 
-    pub fn remove(&mut self, key: &K) -> Option<V> {
-        let index = self.hash(key);
-        let bucket = &mut self.buckets[index];
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find_index(self.hash(key), key)?;
+        Some(&mut self.raw.bucket_mut(index).value)
+    }
 
-        for (i, (existing_key, _)) in bucket.iter().enumerate() {
-            if existing_key == key {
-                let (_, value) = bucket.remove(i);
-                self.size -= 1;
-                return Some(value);
-            }
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        let hash = self.hash(&key);
+        match self.find_index(hash, &key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { table: self, index }),
+            None => Entry::Vacant(VacantEntry { table: self, hash, key }),
         }
+    }
 
-        None
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: K, default: F) -> &mut V {
+        self.entry(key).or_insert_with(default)
     }
 
-    pub fn contains_key(&self, key: &K) -> bool {
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find_index(self.hash(key), key)?;
+        Some(self.raw.remove(index).value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
         self.get(key).is_some()
     }
 
     pub fn len(&self) -> usize {
-        self.size
+        self.raw.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.size == 0
+        self.raw.len() == 0
     }
 
     pub fn keys(&self) -> impl Iterator<Item = &K> {
-        self.buckets.iter().flat_map(|bucket| bucket.iter().map(|(k, _)| k))
+        self.iter().map(|(k, _)| k)
     }
 
     pub fn values(&self) -> impl Iterator<Item = &V> {
-        self.buckets.iter().flat_map(|bucket| bucket.iter().map(|(_, v)| v))
+        self.iter().map(|(_, v)| v)
     }
 
     pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
-        self.buckets.iter().flat_map(|bucket| bucket.iter().map(|(k, v)| (k, v)))
+        self.raw.iter().map(|bucket| (&bucket.key, &bucket.value))
+    }
+}
+
+// Hashes raw bytes exactly like the `str` they spell, so String keys can be probed with a
+// station name straight from the input without validating or allocating it
+struct StrBytes<'a>(&'a [u8]);
+
+impl Hash for StrBytes<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Same as the default `Hasher::write_str` that `str` hashes through
+        state.write(self.0);
+        state.write_u8(0xff);
     }
 }
 
-impl<K, V> Default for HashTable<K, V>
+impl<V, S> HashTable<String, V, S>
+where
+    V: Clone,
+    S: BuildHasher,
+{
+    pub fn get_bytes(&self, key: &[u8]) -> Option<&V> {
+        let hash = self.hash(&StrBytes(key));
+        let index = self.raw.find(hash, |candidate| candidate.as_bytes() == key)?;
+        Some(&self.raw.bucket(index).value)
+    }
+
+    pub fn get_bytes_mut(&mut self, key: &[u8]) -> Option<&mut V> {
+        let hash = self.hash(&StrBytes(key));
+        let index = self.raw.find(hash, |candidate| candidate.as_bytes() == key)?;
+        Some(&mut self.raw.bucket_mut(index).value)
+    }
+
+    pub fn contains_key_bytes(&self, key: &[u8]) -> bool {
+        self.get_bytes(key).is_some()
+    }
+}
+
+// Moves every key and value out of the table, in slot order
+pub struct IntoIter<K, V> {
+    buckets: <RawTable<K, V> as IntoIterator>::IntoIter,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        self.buckets.next().map(|bucket| (bucket.key, bucket.value))
+    }
+}
+
+impl<K, V, S> IntoIterator for HashTable<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter { buckets: self.raw.into_iter() }
+    }
+}
+
+impl<K, V, S> Default for HashTable<K, V, S>
 where
     K: Hash + Eq + Clone,
     V: Clone,
+    S: BuildHasher + Default,
 {
     fn default() -> Self {
-        Self::new()
+        Self::with_hasher(S::default())
+    }
+}
+
+pub enum Entry<'a, K, V, S = XxHash64Builder> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+pub struct OccupiedEntry<'a, K, V, S = XxHash64Builder> {
+    table: &'a mut HashTable<K, V, S>,
+    index: usize,
+}
+
+pub struct VacantEntry<'a, K, V, S = XxHash64Builder> {
+    table: &'a mut HashTable<K, V, S>,
+    hash: u64,
+    key: K,
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher,
+{
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let value = default(&entry.key);
+                entry.insert(value)
+            }
+        }
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.table.raw.bucket(self.index).key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.table.raw.bucket(self.index).value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.table.raw.bucket_mut(self.index).value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.table.raw.bucket_mut(self.index).value
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+}
+
+impl<'a, K, V, S> VacantEntry<'a, K, V, S>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher,
+{
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    // Only grows the table once a new key is actually added
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.table.raw.reserve_one();
+        let index = self.table.raw.insert_new(self.hash, self.key, value);
+        &mut self.table.raw.bucket_mut(index).value
     }
 }
 
@@ -225,4 +395,200 @@ mod tests {
         let pairs: Vec<_> = table.iter().collect();
         assert_eq!(pairs.len(), 3);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_capacity_is_power_of_two() {
+        let table: HashTable<u32, u32> = HashTable::with_capacity(10);
+        assert_eq!(table.capacity(), 16);
+
+        let table: HashTable<u32, u32> = HashTable::with_capacity(0);
+        assert_eq!(table.capacity(), 1);
+    }
+
+    #[test]
+    fn test_updates_at_the_load_threshold_do_not_grow() {
+        let mut table = HashTable::with_capacity(4);
+        for i in 0..3u32 {
+            table.insert(i, i);
+        }
+        assert_eq!(table.capacity(), 4);
+
+        assert_eq!(table.insert(1, 10), Some(1));
+        *table.entry(2).or_insert(0) += 10;
+        assert!(matches!(table.entry(3), Entry::Vacant(_)));
+        assert_eq!(table.capacity(), 4);
+        assert_eq!((table.get(&1), table.get(&2)), (Some(&10), Some(&12)));
+
+        table.insert(3, 3);
+        assert_eq!(table.capacity(), 8);
+    }
+
+    #[test]
+    fn test_remove_keeps_probe_chains_intact() {
+        let mut table = HashTable::with_capacity(4);
+        let mut expected = std::collections::HashMap::new();
+
+        for i in 0..500u32 {
+            table.insert(i, i * 2);
+            expected.insert(i, i * 2);
+        }
+
+        for i in (0..500u32).filter(|i| i % 3 != 0) {
+            assert_eq!(table.remove(&i), expected.remove(&i));
+        }
+
+        assert_eq!(table.len(), expected.len());
+        for i in 0..500u32 {
+            assert_eq!(table.get(&i), expected.get(&i));
+        }
+
+        for i in 0..500u32 {
+            table.insert(i, i);
+        }
+        assert_eq!(table.len(), 500);
+        assert_eq!(table.iter().count(), 500);
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut table = HashTable::new();
+        table.insert("key".to_string(), 42);
+
+        *table.get_mut(&"key".to_string()).unwrap() += 1;
+        assert_eq!(table.get(&"key".to_string()), Some(&43));
+        assert!(table.get_mut(&"missing".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_entry_api() {
+        let mut table = HashTable::new();
+
+        *table.entry("a".to_string()).or_insert(0) += 1;
+        *table.entry("a".to_string()).or_insert(0) += 1;
+        assert_eq!(table.get(&"a".to_string()), Some(&2));
+
+        table.entry("b".to_string())
+            .and_modify(|value| *value += 100)
+            .or_insert_with(|| 7);
+        table.entry("b".to_string())
+            .and_modify(|value| *value += 100)
+            .or_insert_with(|| 7);
+        assert_eq!(table.get(&"b".to_string()), Some(&107));
+
+        let value = table.entry("abc".to_string()).or_insert_with_key(|key| key.len());
+        assert_eq!(*value, 3);
+
+        match table.entry("a".to_string()) {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.key(), "a");
+                assert_eq!(entry.insert(10), 2);
+            }
+            Entry::Vacant(_) => panic!("Expected occupied entry"),
+        }
+        assert_eq!(table.get(&"a".to_string()), Some(&10));
+        assert_eq!(table.len(), 3);
+    }
+
+    #[test]
+    fn test_get_or_insert_with_grows_table() {
+        let mut table = HashTable::with_capacity(1);
+
+        for i in 0..100 {
+            *table.get_or_insert_with(i % 10, || 0) += 1;
+        }
+
+        assert_eq!(table.len(), 10);
+        for i in 0..10 {
+            assert_eq!(table.get(&i), Some(&10));
+        }
+    }
+
+    #[test]
+    fn test_borrowed_lookup() {
+        let mut table: HashTable<String, i32> = HashTable::new();
+        table.insert("Hamburg".to_string(), 12);
+        table.insert("Abha".to_string(), 18);
+
+        assert_eq!(table.get("Hamburg"), Some(&12));
+        assert!(table.contains_key("Abha"));
+        assert!(!table.contains_key("Bulawayo"));
+
+        *table.get_mut("Abha").unwrap() += 1;
+        assert_eq!(table.get("Abha"), Some(&19));
+
+        // Byte slices from the input can be probed through their &str view without allocating
+        let line: &[u8] = b"Hamburg;12.0";
+        let station = std::str::from_utf8(&line[..7]).unwrap();
+        assert_eq!(table.get(station), Some(&12));
+
+        assert_eq!(table.remove("Hamburg"), Some(12));
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn test_borrowed_byte_slice_lookup() {
+        let mut table: HashTable<String, i32> = HashTable::new();
+        table.insert("Hamburg".to_string(), 12);
+        table.insert("İzmir".to_string(), 18);
+
+        // Station bytes straight from a line, without a UTF-8 check or an allocation
+        let line: &[u8] = b"Hamburg;12.0";
+        assert_eq!(table.get_bytes(&line[..7]), Some(&12));
+        assert!(!table.contains_key_bytes(&line[..3]));
+        assert!(!table.contains_key_bytes(b"Ham\xffburg"));
+
+        *table.get_bytes_mut("İzmir".as_bytes()).unwrap() += 1;
+        assert_eq!(table.get("İzmir"), Some(&19));
+    }
+
+    #[test]
+    fn test_byte_lookup_hashes_like_str() {
+        use crate::xxhash::{XxHash32Builder, Xxh3_64Builder};
+
+        fn check<S: BuildHasher>(hash_builder: S) {
+            for key in ["", "a", "Hamburg", "São Paulo", "Ho Chi Minh City and then some more"] {
+                assert_eq!(hash_builder.hash_one(StrBytes(key.as_bytes())), hash_builder.hash_one(key), "{:?}", key);
+            }
+        }
+
+        check(XxHash64Builder::default());
+        check(XxHash32Builder::new(7));
+        check(Xxh3_64Builder::default());
+        check(rustc_hash::FxBuildHasher);
+        check(std::collections::hash_map::RandomState::new());
+    }
+
+    #[test]
+    fn test_into_iter_moves_entries() {
+        let mut table = HashTable::with_capacity(2);
+        for i in 0..20 {
+            table.insert(format!("Station{}", i), vec![i]);
+        }
+        table.remove("Station3");
+
+        let mut entries: Vec<(String, Vec<i32>)> = table.into_iter().collect();
+        entries.sort();
+        assert_eq!(entries.len(), 19);
+        assert_eq!(entries[0], ("Station0".to_string(), vec![0]));
+        assert!(entries.iter().all(|(key, value)| *key == format!("Station{}", value[0])));
+    }
+
+    #[test]
+    fn test_custom_hashers() {
+        use crate::xxhash::{XxHash32Builder, Xxh3_64Builder, Xxh3_128Builder};
+
+        fn exercise<S: BuildHasher>(mut table: HashTable<String, usize, S>) {
+            for i in 0..100 {
+                table.insert(format!("Station{}", i), i);
+            }
+            assert_eq!(table.len(), 100);
+            for i in 0..100 {
+                assert_eq!(table.get(format!("Station{}", i).as_str()), Some(&i));
+            }
+        }
+
+        exercise(HashTable::with_hasher(XxHash32Builder::new(7)));
+        exercise(HashTable::with_hasher(Xxh3_64Builder::default()));
+        exercise(HashTable::with_capacity_and_hasher(4, Xxh3_128Builder::new(7)));
+    }
+}
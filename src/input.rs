@@ -0,0 +1,124 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use crate::parser::{ParsedRecord, RecordSource};
+use crate::{ErrorPolicy, ErrorReport, MmapWeatherCsvReader, WeatherCsvReader, WeatherError};
+
+// Command-line path that means "read standard input"
+pub const STDIN_PATH: &str = "-";
+
+// Read size used when the input can't be memory-mapped
+pub const STREAM_CHUNK_SIZE: usize = 1 << 20;
+
+// A weather input opened the fastest way its file type allows: regular files are
+// memory-mapped, while stdin, pipes, FIFOs and devices are read in buffered chunks
+pub enum WeatherInput {
+    Mmap(MmapWeatherCsvReader),
+    Stream(WeatherCsvReader<Box<dyn Read + Send>>),
+}
+
+impl WeatherInput {
+    // Opens `path`, or standard input when `path` is "-"
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, WeatherError> {
+        let path = path.as_ref();
+        if path.as_os_str() == STDIN_PATH {
+            return Ok(Self::stdin());
+        }
+
+        let file = File::open(path)?;
+        if file.metadata()?.is_file() {
+            Ok(Self::Mmap(MmapWeatherCsvReader::from_file(&file)?))
+        } else {
+            Ok(Self::from_reader(file))
+        }
+    }
+
+    pub fn stdin() -> Self {
+        Self::from_reader(std::io::stdin())
+    }
+
+    pub fn from_reader<R: Read + Send + 'static>(reader: R) -> Self {
+        Self::Stream(WeatherCsvReader::with_capacity(STREAM_CHUNK_SIZE, Box::new(reader)))
+    }
+
+    pub fn with_error_policy(self, policy: ErrorPolicy) -> Self {
+        match self {
+            Self::Mmap(reader) => Self::Mmap(reader.with_error_policy(policy)),
+            Self::Stream(reader) => Self::Stream(reader.with_error_policy(policy)),
+        }
+    }
+
+    pub fn is_mmap(&self) -> bool {
+        matches!(self, Self::Mmap(_))
+    }
+
+    pub fn into_error_report(self) -> ErrorReport {
+        match self {
+            Self::Mmap(reader) => reader.into_error_report(),
+            Self::Stream(reader) => reader.into_error_report(),
+        }
+    }
+}
+
+impl RecordSource for WeatherInput {
+    #[inline]
+    fn next_record(&mut self) -> Option<Result<ParsedRecord<'_>, WeatherError>> {
+        match self {
+            Self::Mmap(reader) => reader.next_record(),
+            Self::Stream(reader) => reader.next_record(),
+        }
+    }
+
+    fn error_report(&self) -> &ErrorReport {
+        match self {
+            Self::Mmap(reader) => reader.error_report(),
+            Self::Stream(reader) => reader.error_report(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const TEST_FILE: &str = "data/measurements_1KRecords.txt";
+
+    #[test]
+    fn test_regular_file_is_mapped() {
+        let mut input = WeatherInput::open(TEST_FILE).unwrap();
+        assert!(input.is_mmap());
+        assert_eq!(input.collect_records().unwrap().len(), 1000);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_device_is_streamed() {
+        let mut input = WeatherInput::open("/dev/null").unwrap();
+        assert!(!input.is_mmap());
+        assert!(input.collect_records().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_stream_matches_mmap() {
+        let data = std::fs::read(TEST_FILE).unwrap();
+        let mut streamed = WeatherInput::from_reader(Cursor::new(data));
+        let mut mapped = WeatherInput::open(TEST_FILE).unwrap();
+        assert!(!streamed.is_mmap());
+        assert_eq!(streamed.collect_records().unwrap(), mapped.collect_records().unwrap());
+    }
+
+    #[test]
+    fn test_stream_applies_error_policy() {
+        let data = b"Hamburg;12.0\nBulawayo 8.9\nHamburg;-3.4".to_vec();
+        let mut input = WeatherInput::from_reader(Cursor::new(data)).with_error_policy(ErrorPolicy::lenient());
+        let records = input.aggregate().unwrap();
+        assert_eq!(records.get("Hamburg").unwrap().count, 2);
+        assert_eq!(input.into_error_report().len(), 1);
+    }
+
+    #[test]
+    fn test_missing_file() {
+        assert!(matches!(WeatherInput::open("data/does_not_exist.txt"), Err(WeatherError::Io(_))));
+    }
+}
@@ -1,11 +1,28 @@
+mod raw_table;
 mod hash_table;
+mod byte_table;
 mod xxhash;
 mod weather;
+mod parser;
+mod input;
+mod paths;
 mod processor;
 mod pipeline;
+mod scanner;
+mod generator;
+mod corruption;
+mod reference;
 
-pub use hash_table::HashTable;
-pub use xxhash::{XxHash32, XxHash64};
-pub use weather::{WeatherRecord, WeatherCsvReader, WeatherStats, WeatherError, StationStats, MmapWeatherCsvReader};
-pub use processor::{read_weather_file, process_weather_file_silent, read_weather_file_mmap, process_weather_file_silent_mmap};
-pub use pipeline::*;
\ No newline at end of file
+pub use hash_table::{HashTable, Entry, OccupiedEntry, VacantEntry, IntoIter};
+pub use byte_table::{ByteHashTable, BytesHasher, ByteIntoIter};
+pub use xxhash::{XxHash32, XxHash64, XxHash32Builder, XxHash64Builder, Xxh3_64, Xxh3_128, Xxh3_64Builder, Xxh3_128Builder};
+pub use weather::{WeatherRecord, WeatherRecordRef, WeatherRecordFixed, WeatherCsvReader, WeatherStats, WeatherStatsAccumulator, WeatherError, StationStats, StationStatsExact, StationStatsFixed, MmapWeatherCsvReader, ErrorKind, ErrorPolicy, ErrorReport, ParseError, SkippedLine, StationAccumulator, StationVarianceStats, TemperatureHistogram, neumaier_add, merge_moments, StationHistogramStats, HISTOGRAM_MIN_TENTHS, HISTOGRAM_MAX_TENTHS, HISTOGRAM_BUCKETS};
+pub use parser::{LineParser, ParsedRecord, RecordSource, SliceRecordSource};
+pub use input::{WeatherInput, STDIN_PATH, STREAM_CHUNK_SIZE};
+pub use paths::{expand_input_paths, glob_match, is_glob_pattern};
+pub use processor::{read_weather_file, process_weather_file_silent, read_weather_file_mmap, process_weather_file_silent_mmap, read_weather_file_with_format, read_weather_file_with_policy, read_weather_file_mmap_with_format, format_challenge_output, format_challenge_temperature, OutputFormat, format_challenge_output_with_options, read_weather_file_with_options, OutputOptions, process_weather_files_silent, process_weather_files_silent_with, process_weather_file_silent_with, read_weather_files_with_policy, read_weather_files_with_percentiles, read_weather_files_with, read_weather_file_with_pipeline, FileStationStats, MultiFileStationStats};
+pub use pipeline::*;
+pub use scanner::{FusedScanner, FusedRecord, FusedHashBuilder, fused_station_hash};
+pub use generator::{generate_stations, parse_station_list, write_measurements, NameLength, OutputSize, Rng, StationConfig, StationSpec, MAX_STATION_NAME_BYTES};
+pub use corruption::{write_corrupted_measurements, Corruption, CorruptionConfig, Manifest, ManifestEntry};
+pub use reference::{compare_references, oracle_reference, Reference, ReferenceStats, StationDiff};
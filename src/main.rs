@@ -1,20 +1,213 @@
 use std::env;
 use std::process;
-use obr::read_weather_file;
+use obr::{
+    expand_input_paths, find_pipeline, read_weather_file_with_pipeline, read_weather_files_with, ErrorPolicy, ErrorReport, OutputOptions,
+    PipelineEntry, StationAccumulator, StationHistogramStats, StationStats, StationVarianceStats, WeatherError, PIPELINES, STDIN_PATH,
+};
+
+// Maximum number of skipped lines listed individually in the summary
+const MAX_LISTED_SKIPPED_LINES: usize = 10;
+
+fn print_usage(program: &str) {
+    eprintln!("Usage: {} [--format csv|challenge] [--lenient] [--max-errors N] [--per-file] [--threads N] [--variance] [--percentiles P,...] <path | glob | dir | ->...", program);
+    eprintln!("       {} [--format csv|challenge] --pipeline NAME <file>", program);
+    eprintln!("       {} --list-pipelines", program);
+    eprintln!("Example: {} weather_data.csv", program);
+    eprintln!("Example: {} --per-file 'shards/2024-*.txt' extra_shards/", program);
+    eprintln!("Example: zcat measurements.txt.gz | {} -", program);
+    eprintln!("Example: {} --format challenge measurements.txt", program);
+    eprintln!("Example: {} --lenient --max-errors 100 weather_data.csv", program);
+    eprintln!("Example: {} --variance --percentiles 50,90,99 measurements.txt", program);
+    eprintln!("Example: {} --pipeline mmap-fused measurements.txt", program);
+}
+
+// (path, report) for every file, so the summary doesn't depend on the stats type
+fn read_files<A: StationAccumulator>(
+    file_paths: &[String],
+    options: &OutputOptions,
+    policy: ErrorPolicy,
+    threads: usize,
+) -> Result<Vec<(String, ErrorReport)>, WeatherError> {
+    let results = read_weather_files_with::<A, _>(file_paths, options, policy, threads)?;
+    Ok(results.files.into_iter().map(|file| (file.path, file.error_report)).collect())
+}
+
+fn print_pipelines() {
+    let width = PIPELINES.iter().map(|pipeline| pipeline.name.len()).max().unwrap_or(0);
+    for pipeline in PIPELINES {
+        println!("{:width$}  {}", pipeline.name, pipeline.description, width = width);
+    }
+}
+
+// Parses a comma-separated list of percentiles such as "50,90,99.9"
+fn parse_percentiles(value: &str) -> Option<Vec<f64>> {
+    value
+        .split(',')
+        .map(|part| part.trim().parse::<f64>().ok().filter(|p| (0.0..=100.0).contains(p)))
+        .collect()
+}
+
+fn print_skipped_summary(file_path: Option<&str>, report: &ErrorReport) {
+    if report.is_empty() {
+        return;
+    }
+    
+    match file_path {
+        Some(file_path) => eprintln!("Skipped {} malformed line(s) in {}:", report.len(), file_path),
+        None => eprintln!("Skipped {} malformed line(s):", report.len()),
+    }
+    for skipped in report.skipped().iter().take(MAX_LISTED_SKIPPED_LINES) {
+        eprintln!("  line {} (byte {}): {}: {:?}",
+                  skipped.line_number,
+                  skipped.byte_offset,
+                  skipped.kind,
+                  String::from_utf8_lossy(&skipped.content));
+    }
+    if report.len() > MAX_LISTED_SKIPPED_LINES {
+        eprintln!("  ... and {} more", report.len() - MAX_LISTED_SKIPPED_LINES);
+    }
+}
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    
-    if args.len() != 2 {
-        eprintln!("Usage: {} <weather_file.csv>", args[0]);
-        eprintln!("Example: {} weather_data.csv", args[0]);
+    let mut options = OutputOptions::default();
+    let mut policy = ErrorPolicy::Strict;
+    let mut threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let mut inputs: Vec<&String> = Vec::new();
+    let mut pipeline: Option<&PipelineEntry> = None;
+
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--format" => {
+                i += 1;
+                let Some(value) = args.get(i) else {
+                    print_usage(&args[0]);
+                    process::exit(1);
+                };
+                options.format = match value.parse() {
+                    Ok(format) => format,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        process::exit(1);
+                    }
+                };
+            }
+            "--lenient" => {
+                if policy == ErrorPolicy::Strict {
+                    policy = ErrorPolicy::lenient();
+                }
+            }
+            "--max-errors" => {
+                i += 1;
+                match args.get(i).map(|value| value.parse::<usize>()) {
+                    Some(Ok(max_errors)) => policy = ErrorPolicy::lenient_with_limit(max_errors),
+                    _ => {
+                        eprintln!("Error: --max-errors expects a non-negative integer");
+                        process::exit(1);
+                    }
+                }
+            }
+            "--per-file" => options.per_file = true,
+            "--variance" => options.variance = true,
+            "--threads" => {
+                i += 1;
+                match args.get(i).map(|value| value.parse::<usize>()) {
+                    Some(Ok(count)) if count > 0 => threads = count,
+                    _ => {
+                        eprintln!("Error: --threads expects a positive integer");
+                        process::exit(1);
+                    }
+                }
+            }
+            "--percentiles" => {
+                i += 1;
+                match args.get(i).and_then(|value| parse_percentiles(value)) {
+                    Some(values) => options.percentiles = values,
+                    None => {
+                        eprintln!("Error: --percentiles expects comma-separated numbers between 0 and 100");
+                        process::exit(1);
+                    }
+                }
+            }
+            "--pipeline" => {
+                i += 1;
+                let Some(name) = args.get(i) else {
+                    print_usage(&args[0]);
+                    process::exit(1);
+                };
+                match find_pipeline(name) {
+                    Some(entry) => pipeline = Some(entry),
+                    None => {
+                        eprintln!("Error: unknown pipeline '{}' (see --list-pipelines)", name);
+                        process::exit(1);
+                    }
+                }
+            }
+            "--list-pipelines" => {
+                print_pipelines();
+                return;
+            }
+            "-" => inputs.push(&args[i]),
+            arg if arg.starts_with("--") => {
+                print_usage(&args[0]);
+                process::exit(1);
+            }
+            _ => inputs.push(&args[i]),
+        }
+        i += 1;
+    }
+
+    if inputs.is_empty() {
+        print_usage(&args[0]);
         process::exit(1);
     }
-    
-    let file_path = &args[1];
-    
-    match read_weather_file(file_path) {
-        Ok(()) => {},
+
+    let file_paths = match expand_input_paths(&inputs) {
+        Ok(file_paths) => file_paths,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    };
+
+    if let Some(pipeline) = pipeline {
+        // Pipelines read one file by path under the strict policy
+        let unsupported = [
+            (policy != ErrorPolicy::Strict, "--lenient and --max-errors"),
+            (options.variance, "--variance"),
+            (!options.percentiles.is_empty(), "--percentiles"),
+            (options.per_file, "--per-file"),
+            (file_paths.len() != 1, "anything but a single input file"),
+            (file_paths.first().is_some_and(|path| path == STDIN_PATH), "stdin"),
+        ];
+        if let Some((_, what)) = unsupported.iter().find(|(used, _)| *used) {
+            eprintln!("Error: --pipeline does not support {}", what);
+            process::exit(1);
+        }
+
+        if let Err(e) = read_weather_file_with_pipeline(&file_paths[0], pipeline, &options) {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    // Only pay for moments and histograms when their columns are printed
+    let reports = match (options.variance, options.percentiles.is_empty()) {
+        (false, true) => read_files::<StationStats>(&file_paths, &options, policy, threads),
+        (true, true) => read_files::<StationVarianceStats>(&file_paths, &options, policy, threads),
+        (false, false) => read_files::<StationHistogramStats>(&file_paths, &options, policy, threads),
+        (true, false) => read_files::<StationHistogramStats<StationVarianceStats>>(&file_paths, &options, policy, threads),
+    };
+
+    match reports {
+        Ok(reports) => {
+            let name_files = reports.len() > 1;
+            for (path, report) in &reports {
+                print_skipped_summary(name_files.then_some(path.as_str()), report);
+            }
+        }
         Err(e) => {
             eprintln!("Error: {}", e);
             process::exit(1);
@@ -0,0 +1,723 @@
+use std::str::FromStr;
+use crate::{parse_temperature_fixed, ErrorKind, ErrorPolicy, ErrorReport, HashTable, ParseError, StationAccumulator, StationStats, WeatherError, WeatherRecord};
+
+// A parsed line borrowing its station name from the input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParsedRecord<'a> {
+    pub station: &'a str,
+    pub temperature: f64,
+}
+
+impl<'a> ParsedRecord<'a> {
+    pub fn to_record(&self) -> WeatherRecord {
+        WeatherRecord::new(self.station.to_string(), self.temperature)
+    }
+}
+
+// The single set of `station;temperature` rules shared by every reader:
+// - surrounding ASCII whitespace (including a trailing '\r') is ignored, blank lines are skipped
+// - a UTF-8 byte order mark at the start of line 1 is ignored
+// - exactly one ';' separates a non-empty station name from the temperature
+// - station names must be valid UTF-8
+// - temperatures must be finite decimal numbers
+#[derive(Debug, Clone, Copy)]
+pub struct LineParser {
+    validate_utf8: bool,
+}
+
+impl Default for LineParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineParser {
+    pub const fn new() -> Self {
+        Self { validate_utf8: true }
+    }
+
+    /// Skips UTF-8 validation of station names.
+    ///
+    /// # Safety
+    ///
+    /// Every line handed to the parser must be valid UTF-8.
+    pub const unsafe fn new_unchecked() -> Self {
+        Self { validate_utf8: false }
+    }
+
+    // Parses one line without its '\n'; returns Ok(None) for blank lines
+    #[inline]
+    pub fn parse<'a>(&self, line_number: usize, line_offset: usize, line: &'a [u8]) -> Result<Option<ParsedRecord<'a>>, ParseError> {
+        match self.find_delimiter(line_number, line_offset, line)? {
+            Some(semicolon) => self.parse_fields(line_number, line_offset, line, semicolon).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // Same rules, keeping the temperature as fixed-point tenths (`-?\d{1,2}\.\d` only)
+    #[inline]
+    pub fn parse_fixed<'a>(&self, line_number: usize, line_offset: usize, line: &'a [u8]) -> Result<Option<(&'a str, i16)>, ParseError> {
+        let Some(semicolon) = self.find_delimiter(line_number, line_offset, line)? else {
+            return Ok(None);
+        };
+        let (station, temperature) = self.split_fields(line_number, line_offset, line, semicolon)?;
+        match parse_temperature_fixed(temperature) {
+            Some(tenths) => Ok(Some((station, tenths))),
+            None => Err(temperature_error(line_number, line_offset, line, temperature)),
+        }
+    }
+
+    // Returns the position of the first ';', or Ok(None) for a blank line
+    #[inline]
+    pub fn find_delimiter(&self, line_number: usize, line_offset: usize, line: &[u8]) -> Result<Option<usize>, ParseError> {
+        if let Some(semicolon) = memchr::memchr(b';', line) {
+            return Ok(Some(semicolon));
+        }
+
+        let trimmed = skip_bom(line_number, line).trim_ascii();
+        if trimmed.is_empty() {
+            return Ok(None);
+        }
+        Err(ParseError::in_line(ErrorKind::MissingDelimiter, line_number, line_offset, line, trimmed))
+    }
+
+    // Applies the field rules once the first ';' is known (the fused scanner finds it itself)
+    #[inline]
+    pub fn parse_fields<'a>(&self, line_number: usize, line_offset: usize, line: &'a [u8], semicolon: usize) -> Result<ParsedRecord<'a>, ParseError> {
+        let (station, temperature_bytes) = self.split_fields(line_number, line_offset, line, semicolon)?;
+
+        let temperature = match parse_temperature_fixed(temperature_bytes) {
+            Some(tenths) => tenths as f64 / 10.0,
+            None => parse_float(temperature_bytes)
+                .ok_or_else(|| temperature_error(line_number, line_offset, line, temperature_bytes))?,
+        };
+
+        Ok(ParsedRecord { station, temperature })
+    }
+
+    #[inline]
+    fn split_fields<'a>(&self, line_number: usize, line_offset: usize, line: &'a [u8], semicolon: usize) -> Result<(&'a str, &'a [u8]), ParseError> {
+        let rest = &line[semicolon + 1..];
+        if let Some(extra) = memchr::memchr(b';', rest) {
+            // Point at the second delimiter and everything after it
+            return Err(ParseError::in_line(ErrorKind::TooManyColumns, line_number, line_offset, line, rest[extra..].trim_ascii_end()));
+        }
+
+        let raw_station = &line[..semicolon];
+        let station_bytes = skip_bom(line_number, raw_station).trim_ascii();
+        if station_bytes.is_empty() {
+            return Err(ParseError::in_line(ErrorKind::EmptyStation, line_number, line_offset, line, raw_station));
+        }
+
+        let station = if self.validate_utf8 {
+            std::str::from_utf8(station_bytes)
+                .map_err(|e| ParseError::invalid_utf8(line_number, line_offset, line, station_bytes, e))?
+        } else {
+            // SAFETY: parsers built with `new_unchecked` are only given valid UTF-8
+            unsafe { std::str::from_utf8_unchecked(station_bytes) }
+        };
+
+        Ok((station, rest.trim_ascii()))
+    }
+}
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[inline]
+fn skip_bom(line_number: usize, bytes: &[u8]) -> &[u8] {
+    if line_number == 1 {
+        bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes)
+    } else {
+        bytes
+    }
+}
+
+#[inline]
+fn parse_float(bytes: &[u8]) -> Option<f64> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    f64::from_str(text).ok().filter(|value| value.is_finite())
+}
+
+fn temperature_error(line_number: usize, line_offset: usize, line: &[u8], temperature: &[u8]) -> ParseError {
+    match std::str::from_utf8(temperature) {
+        Ok(_) => ParseError::in_line(ErrorKind::BadNumber, line_number, line_offset, line, temperature),
+        Err(e) => ParseError::invalid_utf8(line_number, line_offset, line, temperature, e),
+    }
+}
+
+// Returns the offset and bytes of the line starting at `*position` and moves past its '\n'
+#[inline]
+pub(crate) fn next_line<'a>(data: &'a [u8], position: &mut usize) -> Option<(usize, &'a [u8])> {
+    let start = *position;
+    if start >= data.len() {
+        return None;
+    }
+
+    let end = memchr::memchr(b'\n', &data[start..]).map_or(data.len(), |offset| start + offset);
+    *position = end + 1;
+    Some((start, &data[start..end]))
+}
+
+// Parses one line and applies the error policy; Ok(None) means the line was skipped
+#[inline]
+pub(crate) fn accept_line<'a>(
+    parser: &LineParser,
+    policy: ErrorPolicy,
+    report: &mut ErrorReport,
+    line_number: usize,
+    line_offset: usize,
+    line: &'a [u8],
+) -> Result<Option<ParsedRecord<'a>>, WeatherError> {
+    match parser.parse(line_number, line_offset, line) {
+        Ok(record) => Ok(record),
+        Err(error) => report.reject(policy, line_offset, line, error).map(|()| None),
+    }
+}
+
+// A stream of records parsed by LineParser. Malformed lines are either returned as errors or
+// skipped into `error_report()`, depending on the source's ErrorPolicy.
+pub trait RecordSource {
+    fn next_record(&mut self) -> Option<Result<ParsedRecord<'_>, WeatherError>>;
+
+    fn error_report(&self) -> &ErrorReport;
+
+    fn collect_records(&mut self) -> Result<Vec<WeatherRecord>, WeatherError> {
+        let mut records = Vec::new();
+        while let Some(record) = self.next_record() {
+            records.push(record?.to_record());
+        }
+        Ok(records)
+    }
+
+    fn aggregate(&mut self) -> Result<HashTable<String, StationStats>, WeatherError>
+    where
+        Self: Sized,
+    {
+        self.aggregate_with()
+    }
+
+    // Same as aggregate, into any per-station accumulator (e.g. StationHistogramStats)
+    fn aggregate_with<A: StationAccumulator>(&mut self) -> Result<HashTable<String, A>, WeatherError>
+    where
+        Self: Sized,
+    {
+        let mut station_stats: HashTable<String, A> = HashTable::new();
+
+        while let Some(record) = self.next_record() {
+            let record = record?;
+
+            // Look up by &str first so existing stations don't allocate a key
+            match station_stats.get_mut(record.station) {
+                Some(stats) => stats.add_temperature(record.temperature),
+                None => {
+                    station_stats.insert(record.station.to_string(), A::new(record.station.to_string(), record.temperature));
+                }
+            }
+        }
+
+        Ok(station_stats)
+    }
+}
+
+// RecordSource over an in-memory buffer (a Vec or an mmap)
+pub struct SliceRecordSource<'a> {
+    data: &'a [u8],
+    position: usize,
+    line_number: usize,
+    parser: LineParser,
+    policy: ErrorPolicy,
+    report: ErrorReport,
+}
+
+impl<'a> SliceRecordSource<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+            line_number: 0,
+            parser: LineParser::new(),
+            policy: ErrorPolicy::Strict,
+            report: ErrorReport::new(),
+        }
+    }
+
+    pub fn with_parser(mut self, parser: LineParser) -> Self {
+        self.parser = parser;
+        self
+    }
+
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn into_error_report(self) -> ErrorReport {
+        self.report
+    }
+}
+
+impl<'a> Iterator for SliceRecordSource<'a> {
+    type Item = Result<ParsedRecord<'a>, WeatherError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((line_offset, line)) = next_line(self.data, &mut self.position) {
+            self.line_number += 1;
+            match accept_line(&self.parser, self.policy, &mut self.report, self.line_number, line_offset, line) {
+                Ok(Some(record)) => return Some(Ok(record)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+}
+
+impl<'a> RecordSource for SliceRecordSource<'a> {
+    fn next_record(&mut self) -> Option<Result<ParsedRecord<'_>, WeatherError>> {
+        self.next()
+    }
+
+    fn error_report(&self) -> &ErrorReport {
+        &self.report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use std::io::Cursor;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Records in file order, or the (kind, line, column, byte offset) of the first rejected line
+    type Outcome = Result<Vec<(String, f64)>, (ErrorKind, usize, usize, usize)>;
+
+    struct Case {
+        name: &'static str,
+        input: &'static [u8],
+        expected: Outcome,
+    }
+
+    fn records(pairs: &[(&str, f64)]) -> Outcome {
+        Ok(pairs.iter().map(|&(station, temperature)| (station.to_string(), temperature)).collect())
+    }
+
+    fn cases() -> Vec<Case> {
+        let ok = |name, input, pairs: &[(&str, f64)]| Case { name, input, expected: records(pairs) };
+        let err = |name, input, kind, line, column, byte_offset| Case { name, input, expected: Err((kind, line, column, byte_offset)) };
+        vec![
+            ok("basic", b"Hamburg;12.0\nBulawayo;8.9\nHamburg;-3.4\n", &[("Hamburg", 12.0), ("Bulawayo", 8.9), ("Hamburg", -3.4)]),
+            ok("no trailing newline", b"Hamburg;12.0\nBulawayo;8.9", &[("Hamburg", 12.0), ("Bulawayo", 8.9)]),
+            ok("crlf", b"Hamburg;12.0\r\nBulawayo;8.9\r\n", &[("Hamburg", 12.0), ("Bulawayo", 8.9)]),
+            ok("blank lines", b"\nHamburg;12.0\n\n   \nBulawayo;8.9\n\n", &[("Hamburg", 12.0), ("Bulawayo", 8.9)]),
+            ok("whitespace padding", b"  Hamburg  ;  12.0  \n\tBulawayo;8.9\t\n", &[("Hamburg", 12.0), ("Bulawayo", 8.9)]),
+            ok("unicode stations", "São Paulo;25.1\nİzmir;-0.5\n".as_bytes(), &[("São Paulo", 25.1), ("İzmir", -0.5)]),
+            ok("empty input", b"", &[]),
+            ok("byte order mark", b"\xEF\xBB\xBFHamburg;12.0\nBulawayo;8.9\n", &[("Hamburg", 12.0), ("Bulawayo", 8.9)]),
+            err("missing delimiter", b"Hamburg;12.0\nBulawayo 8.9\n", ErrorKind::MissingDelimiter, 2, 1, 13),
+            err("too many columns", b"Hamburg;12.0;extra\n", ErrorKind::TooManyColumns, 1, 13, 12),
+            err("empty station", b";12.0\n", ErrorKind::EmptyStation, 1, 1, 0),
+            err("blank station", b"   ;12.0\n", ErrorKind::EmptyStation, 1, 1, 0),
+            err("bad number", b"Hamburg;abc\n", ErrorKind::BadNumber, 1, 9, 8),
+            err("empty temperature", b"Hamburg;\n", ErrorKind::BadNumber, 1, 9, 8),
+            err("non-finite temperature", b"Hamburg;NaN\n", ErrorKind::BadNumber, 1, 9, 8),
+            err("invalid UTF-8 in temperature", b"Hamburg;1\xff.0\n", ErrorKind::InvalidUtf8, 1, 10, 9),
+            err("invalid UTF-8 in station", b"Hamburg;12.0\nHam\xffburg;12.0\n", ErrorKind::InvalidUtf8, 2, 4, 16),
+            err("error after blank lines", b"\nHamburg;12.0\n\n  \r\nBulawayo;x\n", ErrorKind::BadNumber, 5, 10, 28),
+            err(
+                "error in a later chunk",
+                b"Hamburg;12.0\nBulawayo;8.9\nHamburg;-3.4\nBulawayo;1.5\nHamburg;0.1\nBulawayo;2.5\nHamburg;1;2\n",
+                ErrorKind::TooManyColumns, 7, 10, 86,
+            ),
+        ]
+    }
+
+    fn outcome<T>(result: Result<Vec<T>, WeatherError>, to_pair: impl Fn(T) -> (String, f64)) -> Outcome {
+        match result {
+            Ok(records) => Ok(records.into_iter().map(to_pair).collect()),
+            Err(WeatherError::Parse(error)) => Err((error.kind, error.line, error.column, error.byte_offset)),
+            Err(other) => panic!("Unexpected error: {}", other),
+        }
+    }
+
+    fn owned(record: WeatherRecord) -> (String, f64) {
+        (record.station, record.temperature)
+    }
+
+    fn borrowed(record: WeatherRecordRef<'_>) -> (String, f64) {
+        (String::from_utf8(record.station.to_vec()).unwrap(), record.temperature)
+    }
+
+    fn fixed(record: WeatherRecordFixed) -> (String, f64) {
+        (record.station, record.temperature as f64 / 10.0)
+    }
+
+    fn with_temp_file<T>(data: &[u8], f: impl FnOnce(&str) -> T) -> T {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "obr_conformance_{}_{}.txt",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&path, data).unwrap();
+        let result = f(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        result
+    }
+
+    // (name, run on input, validates station UTF-8); the `unsafe` parsers are only given
+    // input that is valid UTF-8
+    fn allowed(validates_utf8: bool, input: &[u8]) -> bool {
+        validates_utf8 || std::str::from_utf8(input).is_ok()
+    }
+
+    type NamedSource = (&'static str, fn(&[u8]) -> Outcome, bool);
+    type NamedPipeline = (&'static str, fn(&str) -> Result<HashTable<String, StationStats>, WeatherError>, bool);
+
+    fn record_sources() -> Vec<NamedSource> {
+        vec![
+            ("WeatherCsvReader", |data| outcome(WeatherCsvReader::from_reader(Cursor::new(data)).collect_records(), owned), true),
+            ("MmapWeatherCsvReader", |data| with_temp_file(data, |path| {
+                outcome(MmapWeatherCsvReader::from_path(path).and_then(|mut reader| reader.collect_records()), owned)
+            }), true),
+            ("SliceRecordSource", |data| outcome(SliceRecordSource::new(data).collect_records(), owned), true),
+            ("FusedScanner", |data| outcome(FusedScanner::new(data).collect_records(), owned), true),
+            ("parse_records_string", |data| outcome(parse_records_string(&split_into_lines_simd(data)), owned), true),
+            ("parse_records_bytes", |data| outcome(parse_records_bytes(&split_into_lines_simd(data)), owned), true),
+            // SAFETY: see `allowed`
+            ("parse_records_unsafe", |data| outcome(unsafe { parse_records_unsafe(&split_into_lines_simd(data)) }, owned), false),
+            ("parse_records_bytes_ref", |data| outcome(parse_records_bytes_ref(&split_into_lines_simd(data)), borrowed), true),
+            ("parse_records_unsafe_ref", |data| outcome(unsafe { parse_records_unsafe_ref(&split_into_lines_simd(data)) }, borrowed), false),
+            ("parse_records_fixed", |data| outcome(parse_records_fixed(&split_into_lines_simd(data)), fixed), true),
+            ("parse_records_bytes_with_policy", |data| {
+                outcome(parse_records_bytes_with_policy(data, ErrorPolicy::Strict).map(|(records, _)| records), owned)
+            }, true),
+            ("parse_records_bytes_ref_with_policy", |data| {
+                outcome(parse_records_bytes_ref_with_policy(data, ErrorPolicy::Strict).map(|(records, _)| records), borrowed)
+            }, true),
+            ("parse_records_fixed_with_policy", |data| {
+                outcome(parse_records_fixed_with_policy(data, ErrorPolicy::Strict).map(|(records, _)| records), fixed)
+            }, true),
+        ]
+    }
+
+    // Pipelines only expose aggregates, so they are compared on per-station (count, min, max),
+    // or on the (kind, line, column, byte offset) of the error, chunked ones included
+    type PipelineOutcome = Result<Vec<(String, usize, f64, f64)>, (ErrorKind, usize, usize, usize)>;
+
+    fn pipeline_outcome(result: Result<HashTable<String, StationStats>, WeatherError>) -> PipelineOutcome {
+        match result {
+            Ok(table) => {
+                let mut stations: Vec<_> = table
+                    .iter()
+                    .map(|(station, stats)| (station.clone(), stats.count, stats.min_temperature, stats.max_temperature))
+                    .collect();
+                stations.sort_by(|a, b| a.0.cmp(&b.0));
+                Ok(stations)
+            }
+            Err(WeatherError::Parse(error)) => Err((error.kind, error.line, error.column, error.byte_offset)),
+            Err(other) => panic!("Unexpected error: {}", other),
+        }
+    }
+
+    fn pipelines() -> Vec<NamedPipeline> {
+        vec![
+            ("pipeline_current", pipeline_current, true),
+            ("pipeline_mmap_string", pipeline_mmap_string, true),
+            ("pipeline_mmap_bytes", pipeline_mmap_bytes, true),
+            // SAFETY: see `allowed`
+            ("pipeline_mmap_unsafe", |path| unsafe { pipeline_mmap_unsafe(path) }, false),
+            ("pipeline_mmap_unsafe_validated", pipeline_mmap_unsafe_validated, true),
+            ("pipeline_mmap_bytes_ref", pipeline_mmap_bytes_ref, true),
+            ("pipeline_mmap_unsafe_ref", |path| unsafe { pipeline_mmap_unsafe_ref(path) }, false),
+            ("pipeline_mmap_unsafe_ref_validated", pipeline_mmap_unsafe_ref_validated, true),
+            ("pipeline_mmap_byte_table", pipeline_mmap_byte_table, true),
+            ("pipeline_mmap_fused", pipeline_mmap_fused, true),
+            ("pipeline_buffered_bytes", pipeline_buffered_bytes, true),
+            ("pipeline_streaming", pipeline_streaming, true),
+            ("pipeline_parallel", |path| pipeline_parallel(path, 3), true),
+        ]
+    }
+
+    #[test]
+    fn test_line_parser_rules() {
+        let parser = LineParser::new();
+        assert_eq!(parser.parse(1, 0, b"  \r").unwrap(), None);
+        assert_eq!(
+            parser.parse(1, 0, b" Hamburg ; -12.3 \r").unwrap(),
+            Some(ParsedRecord { station: "Hamburg", temperature: -12.3 })
+        );
+        assert_eq!(parser.parse(1, 0, b"Hamburg;1e1").unwrap().unwrap().temperature, 10.0);
+        assert_eq!(parser.parse(1, 0, b"\xEF\xBB\xBFHamburg;1.0").unwrap().unwrap().station, "Hamburg");
+        assert_eq!(parser.parse(2, 0, b"\xEF\xBB\xBFHamburg;1.0").unwrap().unwrap().station, "\u{FEFF}Hamburg");
+        assert_eq!(parser.parse_fixed(1, 0, b"Hamburg;-12.3").unwrap(), Some(("Hamburg", -123)));
+        assert_eq!(parser.parse_fixed(1, 0, b"Hamburg;12").unwrap_err().kind, ErrorKind::BadNumber);
+        
+        let error = parser.parse(7, 100, b"Hamburg;1;2").unwrap_err();
+        assert_eq!((error.kind, error.line, error.column, error.byte_offset), (ErrorKind::TooManyColumns, 7, 10, 109));
+        assert_eq!(error.bytes, b";2");
+    }
+
+    #[test]
+    fn test_record_sources_conformance() {
+        for case in cases() {
+            for (name, source, validates_utf8) in record_sources() {
+                if !allowed(validates_utf8, case.input) {
+                    continue;
+                }
+                assert_eq!(source(case.input), case.expected, "{} on case '{}'", name, case.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_record_sources_conformance_lenient() {
+        let input = b"Hamburg;12.0\nBulawayo 8.9\n;1.0\nHamburg;1;2\nHamburg;abc\nBulawayo;-3.4\n";
+        let expected = vec![
+            (2, ErrorKind::MissingDelimiter),
+            (3, ErrorKind::EmptyStation),
+            (4, ErrorKind::TooManyColumns),
+            (5, ErrorKind::BadNumber),
+        ];
+        let skipped = |report: &ErrorReport| -> Vec<(usize, ErrorKind)> {
+            report.skipped().iter().map(|line| (line.line_number, line.kind)).collect()
+        };
+        
+        let mut reader = WeatherCsvReader::from_reader(Cursor::new(&input[..])).with_error_policy(ErrorPolicy::lenient());
+        assert_eq!(reader.collect_records().unwrap().len(), 2);
+        assert_eq!(skipped(reader.error_report()), expected);
+        
+        let mut source = SliceRecordSource::new(input).with_error_policy(ErrorPolicy::lenient());
+        assert_eq!(source.collect_records().unwrap().len(), 2);
+        assert_eq!(skipped(RecordSource::error_report(&source)), expected);
+        
+        let mut scanner = FusedScanner::new(input).with_error_policy(ErrorPolicy::lenient());
+        assert_eq!(scanner.collect_records().unwrap().len(), 2);
+        assert_eq!(skipped(scanner.error_report()), expected);
+        
+        with_temp_file(input, |path| {
+            let mut reader = MmapWeatherCsvReader::from_path(path).unwrap().with_error_policy(ErrorPolicy::lenient());
+            assert_eq!(reader.collect_records().unwrap().len(), 2);
+            assert_eq!(skipped(reader.error_report()), expected);
+        });
+    }
+
+    #[test]
+    fn test_pipelines_conformance() {
+        for case in cases() {
+            let expected = match &case.expected {
+                Ok(pairs) => {
+                    let records: Vec<WeatherRecord> = pairs.iter().map(|(station, t)| WeatherRecord::new(station.clone(), *t)).collect();
+                    pipeline_outcome(Ok(aggregate_records_std(&records)))
+                }
+                Err(error) => Err(*error),
+            };
+            
+            with_temp_file(case.input, |path| {
+                for (name, pipeline, validates_utf8) in pipelines() {
+                    if !allowed(validates_utf8, case.input) {
+                        continue;
+                    }
+                    assert_eq!(pipeline_outcome(pipeline(path)), expected, "{} on case '{}'", name, case.name);
+                }
+            });
+        }
+    }
+
+    // Differential test: every pipeline must agree with pipeline_current on generated input.
+    // Inputs come from proptest, so a failure is shrunk (mostly by dropping lines) and reported
+    // as the smallest input that still fails.
+    mod differential {
+        use super::*;
+        use std::cell::Cell;
+        use std::fmt;
+        use proptest::prelude::*;
+        use proptest::test_runner::{Config, TestRunner};
+
+        const STATIONS: &[&str] = &["Hamburg", "Bulawayo", "São Paulo", "İzmir", "A", "Ho Chi Minh City", "東京"];
+        const FREE_FORM_TEMPERATURES: &[&str] = &["12", "+3.5", "1e1", "-0.0", "007.5", ".5", "5.", "-12.25", "100.0", "-1234.5"];
+        const BLANKS: &[&str] = &["", " ", "\t", "  \t ", "\r"];
+        const PADDING: &[&str] = &["", "", "", " ", "\t", "  "];
+        const MALFORMED: &[&[u8]] = &[
+            b"Bulawayo 8.9", b"Hamburg;1.0;2.0", b";1.0", b"  ;1.0", b"Hamburg;abc", b"Hamburg;", b"Hamburg;NaN",
+            b"Hamburg;inf", b"Hamburg;1\xff.0", b"Ham\xffburg;1.0",
+        ];
+        const LINE_ENDINGS: &[&[u8]] = &[b"\n", b"\n", b"\n", b"\n", b"\r\n"];
+
+        #[derive(Debug, Clone)]
+        enum Temperature {
+            Tenths(i64),
+            FreeForm(&'static str),
+        }
+
+        #[derive(Debug, Clone)]
+        enum Line {
+            Blank(&'static str),
+            Record { padding: [&'static str; 4], station: &'static str, temperature: Temperature },
+            Malformed(&'static [u8]),
+        }
+
+        #[derive(Clone)]
+        struct RandomInput {
+            bom: bool,
+            lines: Vec<(Line, &'static [u8])>,
+            // Whether the last line keeps its line ending
+            final_newline: bool,
+        }
+
+        impl RandomInput {
+            fn data(&self) -> Vec<u8> {
+                let mut data = Vec::new();
+                if self.bom {
+                    data.extend_from_slice(b"\xEF\xBB\xBF");
+                }
+                for (i, (line, ending)) in self.lines.iter().enumerate() {
+                    match line {
+                        Line::Blank(blank) => data.extend_from_slice(blank.as_bytes()),
+                        Line::Malformed(bytes) => data.extend_from_slice(bytes),
+                        Line::Record { padding, station, temperature } => {
+                            data.extend_from_slice(format!("{}{}{};{}", padding[0], station, padding[1], padding[2]).as_bytes());
+                            match temperature {
+                                Temperature::Tenths(tenths) => crate::generator::push_temperature(&mut data, *tenths as f64 / 10.0),
+                                Temperature::FreeForm(text) => data.extend_from_slice(text.as_bytes()),
+                            }
+                            data.extend_from_slice(padding[3].as_bytes());
+                        }
+                    }
+                    if i + 1 < self.lines.len() || self.final_newline {
+                        data.extend_from_slice(ending);
+                    }
+                }
+                data
+            }
+
+            // Every temperature has the 1BRC shape pipeline_mmap_fixed requires
+            fn fixed_temperatures(&self) -> bool {
+                self.lines.iter().all(|(line, _)| !matches!(line, Line::Record { temperature: Temperature::FreeForm(_), .. }))
+            }
+        }
+
+        // Shows the bytes the pipelines saw rather than the line structure
+        impl fmt::Debug for RandomInput {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "b\"{}\"", self.data().escape_ascii())
+            }
+        }
+
+        fn line(fixed_temperatures: bool, may_fail: bool) -> BoxedStrategy<Line> {
+            let tenths = (-999i64..=999).prop_map(Temperature::Tenths);
+            let temperature = if fixed_temperatures {
+                tenths.boxed()
+            } else {
+                prop_oneof![2 => tenths, 1 => prop::sample::select(FREE_FORM_TEMPERATURES).prop_map(Temperature::FreeForm)].boxed()
+            };
+            let padding = [(); 4].map(|_| prop::sample::select(PADDING));
+            let record = (padding, prop::sample::select(STATIONS), temperature)
+                .prop_map(|(padding, station, temperature)| Line::Record { padding, station, temperature });
+            let blank = prop::sample::select(BLANKS).prop_map(Line::Blank);
+
+            if may_fail {
+                let malformed = prop::sample::select(MALFORMED).prop_map(Line::Malformed);
+                prop_oneof![10 => blank, 7 => malformed, 63 => record].boxed()
+            } else {
+                prop_oneof![1 => blank, 7 => record].boxed()
+            }
+        }
+
+        fn random_input() -> impl Strategy<Value = RandomInput> {
+            (any::<bool>(), prop::bool::weighted(1.0 / 3.0)).prop_flat_map(|(fixed_temperatures, may_fail)| {
+                let lines = prop::collection::vec((line(fixed_temperatures, may_fail), prop::sample::select(LINE_ENDINGS)), 0..40);
+                (prop::bool::weighted(0.1), lines, any::<bool>())
+                    .prop_map(|(bom, lines, final_newline)| RandomInput { bom, lines, final_newline })
+            })
+        }
+
+        // Per-station (count, min, max, mean), or the (kind, line, column, byte offset) of the error
+        // that stopped the pipeline
+        type Summary = Result<Vec<(String, usize, f64, f64, f64)>, (ErrorKind, usize, usize, usize)>;
+
+        fn summarize<A>(result: Result<HashTable<String, A>, WeatherError>, values: impl Fn(&A) -> (usize, f64, f64, f64)) -> Summary
+        where
+            A: Clone,
+        {
+            match result {
+                Ok(table) => {
+                    let mut stations: Vec<_> = table.iter().map(|(station, stats)| {
+                        let (count, min, max, mean) = values(stats);
+                        (station.clone(), count, min, max, mean)
+                    }).collect();
+                    stations.sort_by(|a, b| a.0.cmp(&b.0));
+                    Ok(stations)
+                }
+                Err(WeatherError::Parse(error)) => Err((error.kind, error.line, error.column, error.byte_offset)),
+                Err(other) => panic!("Unexpected error: {}", other),
+            }
+        }
+
+        fn summarize_stats(result: Result<HashTable<String, StationStats>, WeatherError>) -> Summary {
+            summarize(result, |stats| (stats.count, stats.min_temperature, stats.max_temperature, stats.avg_temperature()))
+        }
+
+        // Means may differ in the last bits because sums are added in different orders
+        fn summaries_agree(expected: &Summary, actual: &Summary) -> bool {
+            match (expected, actual) {
+                (Ok(expected), Ok(actual)) => {
+                    expected.len() == actual.len()
+                        && expected.iter().zip(actual).all(|(e, a)| {
+                            e.0 == a.0 && e.1 == a.1 && e.2 == a.2 && e.3 == a.3 && (e.4 - a.4).abs() <= 1e-9
+                        })
+                }
+                (Err(expected), Err(actual)) => expected == actual,
+                _ => false,
+            }
+        }
+
+        #[test]
+        fn test_pipelines_agree_on_random_inputs() {
+            let mut candidates = pipelines();
+            candidates.extend::<[NamedPipeline; 5]>([
+                ("pipeline_parallel (1 thread)", |path| pipeline_parallel(path, 1), true),
+                ("pipeline_parallel (7 threads)", |path| pipeline_parallel(path, 7), true),
+                ("WeatherInput", |path| WeatherInput::open(path)?.aggregate(), true),
+                ("MmapWeatherCsvReader", |path| MmapWeatherCsvReader::from_path(path)?.aggregate(), true),
+                ("SliceRecordSource", |path| SliceRecordSource::new(&std::fs::read(path)?).aggregate(), true),
+            ]);
+            let fixed = |path: &str| {
+                summarize(pipeline_mmap_fixed(path), |stats: &StationStatsFixed| {
+                    (stats.count as usize, stats.min_temperature(), stats.max_temperature(), stats.avg_temperature())
+                })
+            };
+
+            // Driven by hand rather than through `proptest!` so the outcomes can be counted afterwards
+            let mut runner = TestRunner::new(Config { cases: 400, failure_persistence: None, ..Config::default() });
+            let (accepted, rejected) = (Cell::new(0), Cell::new(0));
+            let result = runner.run(&random_input(), |input| {
+                let data = input.data();
+                with_temp_file(&data, |path| {
+                    let expected = summarize_stats(pipeline_current(path));
+                    let outcome = if expected.is_ok() { &accepted } else { &rejected };
+                    outcome.set(outcome.get() + 1);
+
+                    for (name, pipeline, validates_utf8) in &candidates {
+                        if !allowed(*validates_utf8, &data) {
+                            continue;
+                        }
+                        let actual = summarize_stats(pipeline(path));
+                        prop_assert!(summaries_agree(&expected, &actual), "{} disagrees:\n{:?}\n{:?}", name, expected, actual);
+                    }
+                    if input.fixed_temperatures() {
+                        let actual = fixed(path);
+                        prop_assert!(summaries_agree(&expected, &actual), "pipeline_mmap_fixed disagrees:\n{:?}\n{:?}", expected, actual);
+                    }
+                    Ok(())
+                })
+            });
+            if let Err(error) = result {
+                panic!("{}", error);
+            }
+
+            // Both outcomes are exercised, not just one
+            assert!(accepted.get() > 100 && rejected.get() > 20, "accepted {}, rejected {}", accepted.get(), rejected.get());
+        }
+    }
+}
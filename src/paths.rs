@@ -0,0 +1,259 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::{WeatherError, STDIN_PATH};
+
+// Expands command-line inputs into the list of files to process, in order:
+// - "-" (stdin) and plain paths are kept as given
+// - a directory becomes the measurement files (`.txt`, `.csv`) directly inside it, sorted by
+//   name, so e.g. the `.out` expected outputs next to the samples in data/ are left out
+// - a pattern containing `*`, `?` or `[...]` becomes the matching files, sorted by path
+//   (hidden entries only match when the pattern component starts with '.')
+pub fn expand_input_paths<S: AsRef<str>>(inputs: &[S]) -> Result<Vec<String>, WeatherError> {
+    let mut paths = Vec::new();
+
+    for input in inputs {
+        let input = input.as_ref();
+        if input == STDIN_PATH {
+            paths.push(input.to_string());
+        } else if is_glob_pattern(input) {
+            let matches = expand_glob(input)?;
+            if matches.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::NotFound, format!("no files match '{}'", input)).into());
+            }
+            for path in matches {
+                paths.push(into_string(path)?);
+            }
+        } else if Path::new(input).is_dir() {
+            let mut files: Vec<PathBuf> = fs::read_dir(input)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<_, _>>()?;
+            files.retain(|path| path.is_file() && !is_hidden(path) && is_measurement_file(path));
+            files.sort();
+            for path in files {
+                paths.push(into_string(path)?);
+            }
+        } else {
+            paths.push(input.to_string());
+        }
+    }
+
+    Ok(paths)
+}
+
+pub fn is_glob_pattern(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
+
+const MEASUREMENT_EXTENSIONS: &[&str] = &["txt", "csv"];
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name.as_encoded_bytes().starts_with(b"."))
+}
+
+fn is_measurement_file(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| MEASUREMENT_EXTENSIONS.iter().any(|known| extension == *known))
+}
+
+// Inputs are passed on as strings, so a name that isn't valid UTF-8 is an error rather than
+// a lossy copy that would name a different file
+fn into_string(path: PathBuf) -> Result<String, WeatherError> {
+    path.into_os_string().into_string().map_err(|path| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("'{}' is not valid UTF-8", path.to_string_lossy())).into()
+    })
+}
+
+fn expand_glob(pattern: &str) -> Result<Vec<PathBuf>, WeatherError> {
+    let (mut candidates, rest) = match pattern.strip_prefix('/') {
+        Some(rest) => (vec![PathBuf::from("/")], rest),
+        None => (vec![PathBuf::new()], pattern),
+    };
+
+    for component in rest.split('/').filter(|component| !component.is_empty()) {
+        let mut next = Vec::new();
+        for base in &candidates {
+            if !is_glob_pattern(component) {
+                let path = base.join(component);
+                if path.exists() {
+                    next.push(path);
+                }
+                continue;
+            }
+
+            let dir = if base.as_os_str().is_empty() { Path::new(".") } else { base.as_path() };
+            let Ok(entries) = fs::read_dir(dir) else { continue };
+            for entry in entries {
+                let name = entry?.file_name();
+                if name.as_encoded_bytes().starts_with(b".") && !component.starts_with('.') {
+                    continue;
+                }
+                if glob_match(component, &name) {
+                    next.push(base.join(&name));
+                }
+            }
+        }
+        candidates = next;
+    }
+
+    candidates.retain(|path| path.is_file());
+    candidates.sort();
+    Ok(candidates)
+}
+
+// Matches one path component against `*`, `?` and `[abc]` / `[a-z]` / `[!abc]`. The name is
+// matched on its bytes, so names that aren't valid UTF-8 are matched as they are.
+pub fn glob_match(pattern: &str, name: &OsStr) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name = name_units(name);
+
+    let (mut p, mut n) = (0, 0);
+    // Where to resume after the most recent '*': (pattern index after it, name index it covers up to)
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, n));
+                p += 1;
+                continue;
+            }
+            Some('?') => {
+                p += 1;
+                n += 1;
+                continue;
+            }
+            Some('[') => {
+                if let Some((matched, next)) = match_class(&pattern, p, name[n]) {
+                    if matched {
+                        p = next;
+                        n += 1;
+                        continue;
+                    }
+                } else if name[n] == '[' as u32 {
+                    // Unterminated class: treat '[' literally
+                    p += 1;
+                    n += 1;
+                    continue;
+                }
+            }
+            Some(&c) if c as u32 == name[n] => {
+                p += 1;
+                n += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        // Mismatch: let the last '*' swallow one more character
+        match backtrack {
+            Some((star_p, star_n)) => {
+                backtrack = Some((star_p, star_n + 1));
+                p = star_p;
+                n = star_n + 1;
+            }
+            None => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+// The units `?` and `[...]` match one of: every char of the name, and every byte that isn't
+// part of valid UTF-8 on its own (numbered past char::MAX, so it never equals a pattern char)
+fn name_units(name: &OsStr) -> Vec<u32> {
+    let mut units = Vec::new();
+    for chunk in name.as_encoded_bytes().utf8_chunks() {
+        units.extend(chunk.valid().chars().map(u32::from));
+        units.extend(chunk.invalid().iter().map(|&byte| char::MAX as u32 + 1 + byte as u32));
+    }
+    units
+}
+
+// Returns whether `c` is in the class starting at `pattern[start] == '['`, and the index after it
+fn match_class(pattern: &[char], start: usize, c: u32) -> Option<(bool, usize)> {
+    let mut i = start + 1;
+    let negated = matches!(pattern.get(i), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+
+    let mut matched = false;
+    let mut first = true;
+    while let Some(&p) = pattern.get(i) {
+        if p == ']' && !first {
+            return Some((matched != negated, i + 1));
+        }
+        if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|&end| end != ']') {
+            matched |= p as u32 <= c && c <= pattern[i + 2] as u32;
+            i += 3;
+        } else {
+            matched |= p as u32 == c;
+            i += 1;
+        }
+        first = false;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, name: &str) -> bool {
+        glob_match(pattern, OsStr::new(name))
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(matches("*.txt", "measurements_1KRecords.txt"));
+        assert!(matches("measurements_*Records.*", "measurements_1MRecords.txt"));
+        assert!(!matches("*.txt", "bad_weather.csv"));
+        assert!(matches("day-??.csv", "day-07.csv"));
+        assert!(!matches("day-??.csv", "day-7.csv"));
+        assert!(matches("day-[0-2][0-9].csv", "day-17.csv"));
+        assert!(!matches("day-[!0]*.csv", "day-07.csv"));
+        assert!(matches("a*b*c", "axxbyyc"));
+        assert!(!matches("a*b*c", "axxbyy"));
+        assert!(matches("*", ""));
+        assert!(matches("[abc", "[abc"));
+        // `?` takes one character, however many bytes it is
+        assert!(matches("?dö.txt", "Ödö.txt"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_glob_match_non_utf8_names() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let name = OsStr::from_bytes(b"day-\xff.csv");
+        assert!(glob_match("day-?.csv", name));
+        assert!(glob_match("*.csv", name));
+        assert!(!glob_match("day-[a-z].csv", name));
+        assert!(!glob_match("day-\u{ff}.csv", name));
+    }
+
+    #[test]
+    fn test_expand_glob_and_directory() {
+        let globbed = expand_input_paths(&["data/*.csv"]).unwrap();
+        assert_eq!(globbed, vec!["data/bad_weather.csv", "data/sample_weather.csv"]);
+
+        // The .out golden files next to them are not measurements
+        let listed = expand_input_paths(&["data"]).unwrap();
+        assert_eq!(listed, vec![
+            "data/bad_weather.csv",
+            "data/measurements_1KRecords.txt",
+            "data/measurements_rounding.txt",
+            "data/sample_weather.csv",
+        ]);
+
+        let mixed = expand_input_paths(&["-", "data/sample_weather.csv"]).unwrap();
+        assert_eq!(mixed, vec!["-", "data/sample_weather.csv"]);
+    }
+
+    #[test]
+    fn test_expand_glob_without_matches() {
+        let error = expand_input_paths(&["data/*.nothing"]).unwrap_err();
+        assert!(matches!(error, WeatherError::Io(ref e) if e.kind() == io::ErrorKind::NotFound));
+    }
+}
@@ -1,23 +1,24 @@
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read};
-use std::str::FromStr;
+use std::io::Read;
+use std::hash::BuildHasher;
 use memmap2::MmapOptions;
 use memchr::memchr_iter;
 use rustc_hash::FxHashMap;
-use crate::{WeatherRecord, WeatherError, StationStats, HashTable};
+use crate::parser::next_line;
+use crate::{ByteHashTable, BytesHasher, ErrorPolicy, ErrorReport, FusedHashBuilder, FusedScanner, LineParser, ParseError, RecordSource, WeatherRecord, WeatherRecordRef, WeatherRecordFixed, WeatherError, StationStats, StationStatsFixed, StationAccumulator, HashTable, Entry};
 
 // ============================================================================
 // Stage 1: File Reading
 // ============================================================================
 
-pub fn read_file_raw_buffered(file_path: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+pub fn read_file_raw_buffered(file_path: &str) -> Result<Vec<u8>, WeatherError> {
     let mut file = File::open(file_path)?;
     let mut buffer = Vec::new();
     file.read_to_end(&mut buffer)?;
     Ok(buffer)
 }
 
-pub fn read_file_raw_mmap(file_path: &str) -> Result<memmap2::Mmap, Box<dyn std::error::Error>> {
+pub fn read_file_raw_mmap(file_path: &str) -> Result<memmap2::Mmap, WeatherError> {
     let file = File::open(file_path)?;
     let mmap = unsafe { MmapOptions::new().map(&file)? };
     Ok(mmap)
@@ -27,15 +28,15 @@ pub fn read_file_raw_mmap(file_path: &str) -> Result<memmap2::Mmap, Box<dyn std:
 // Stage 2: Line Splitting
 // ============================================================================
 
+// Both splitters keep blank lines, so the n-th slice is line n of the input and parsers
+// can report real line numbers and byte offsets
 pub fn split_into_lines_basic(data: &[u8]) -> Vec<&[u8]> {
     let mut lines = Vec::new();
     let mut start = 0;
     
     for (i, &byte) in data.iter().enumerate() {
         if byte == b'\n' {
-            if start < i {
-                lines.push(&data[start..i]);
-            }
+            lines.push(&data[start..i]);
             start = i + 1;
         }
     }
@@ -53,9 +54,7 @@ pub fn split_into_lines_simd(data: &[u8]) -> Vec<&[u8]> {
     let mut start = 0;
     
     for newline_pos in memchr_iter(b'\n', data) {
-        if start < newline_pos {
-            lines.push(&data[start..newline_pos]);
-        }
+        lines.push(&data[start..newline_pos]);
         start = newline_pos + 1;
     }
     
@@ -67,145 +66,212 @@ pub fn split_into_lines_simd(data: &[u8]) -> Vec<&[u8]> {
     lines
 }
 
+pub fn split_into_chunks(data: &[u8], chunk_count: usize) -> Vec<&[u8]> {
+    let chunk_count = chunk_count.max(1);
+    let target_size = data.len().div_ceil(chunk_count);
+    let mut chunks = Vec::with_capacity(chunk_count);
+    let mut start = 0;
+    
+    while start < data.len() {
+        let mut end = (start + target_size).min(data.len());
+        
+        // Extend the chunk to the end of the line it stops in
+        if end < data.len() {
+            end = match memchr::memchr(b'\n', &data[end..]) {
+                Some(offset) => end + offset + 1,
+                None => data.len(),
+            };
+        }
+        
+        chunks.push(&data[start..end]);
+        start = end;
+    }
+    
+    chunks
+}
+
 // ============================================================================
 // Stage 3: Record Parsing
 // ============================================================================
 
-pub fn parse_records_string(lines: &[&[u8]]) -> Result<Vec<WeatherRecord>, WeatherError> {
-    let mut records = Vec::with_capacity(lines.len());
+// Parses a 1BRC temperature (`-?\d{1,2}\.\d`) into tenths of a degree, e.g. `-12.3` -> `-123`
+#[inline]
+pub fn parse_temperature_fixed(bytes: &[u8]) -> Option<i16> {
+    let (negative, digits) = match bytes.split_first() {
+        Some((b'-', rest)) => (true, rest),
+        _ => (false, bytes),
+    };
     
-    for (line_num, &line_bytes) in lines.iter().enumerate() {
-        // Skip empty lines
-        if line_bytes.is_empty() {
-            continue;
+    let value = match *digits {
+        [units, b'.', tenths] if units.is_ascii_digit() && tenths.is_ascii_digit() => {
+            (units - b'0') as i16 * 10 + (tenths - b'0') as i16
         }
-        
-        let line = std::str::from_utf8(line_bytes)
-            .map_err(|_| WeatherError::InvalidFormat(
-                format!("Line {}: Invalid UTF-8 encoding", line_num + 1)
-            ))?;
-            
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
+        [tens, units, b'.', tenths] if tens.is_ascii_digit() && units.is_ascii_digit() && tenths.is_ascii_digit() => {
+            (tens - b'0') as i16 * 100 + (units - b'0') as i16 * 10 + (tenths - b'0') as i16
         }
+        _ => return None,
+    };
+    
+    Some(if negative { -value } else { value })
+}
 
-        let parts: Vec<&str> = line.split(';').collect();
-        if parts.len() != 2 {
-            return Err(WeatherError::InvalidFormat(
-                format!("Line {} does not have exactly 2 columns separated by ';'. Found {} columns", 
-                       line_num + 1, parts.len())
-            ));
-        }
+// Per-line parser: (line number, byte offset of the line, line bytes), Ok(None) for blank lines
+type LineParserFn<'a, T> = fn(usize, usize, &'a [u8]) -> Result<Option<T>, ParseError>;
 
-        let station = parts[0].trim().to_string();
-        if station.is_empty() {
-            return Err(WeatherError::InvalidFormat(
-                format!("Line {}: Weather station name cannot be empty", line_num + 1)
-            ));
-        }
+const LINE_PARSER: LineParser = LineParser::new();
 
-        let temperature_str = parts[1].trim();
-        let temperature = f64::from_str(temperature_str)
-            .map_err(|_| WeatherError::Parse(
-                format!("Line {}: Cannot parse temperature '{}' as a number", 
-                       line_num + 1, temperature_str)
-            ))?;
+// Used where the input is already known to be valid UTF-8
+const UNCHECKED_LINE_PARSER: LineParser = unsafe { LineParser::new_unchecked() };
 
-        records.push(WeatherRecord::new(station, temperature));
-    }
-    
-    Ok(records)
-}
-
-pub fn parse_records_bytes(lines: &[&[u8]]) -> Result<Vec<WeatherRecord>, WeatherError> {
+// Runs a per-line parser over every line of the input in order, blank ones included (as the
+// splitters above return them), failing on the first malformed line
+fn parse_lines<'a, T>(lines: &[&'a [u8]], parse_line: LineParserFn<'a, T>) -> Result<Vec<T>, WeatherError> {
     let mut records = Vec::with_capacity(lines.len());
+    let mut line_offset = 0;
     
     for (line_num, &line_bytes) in lines.iter().enumerate() {
-        // Skip empty lines
-        if line_bytes.is_empty() {
-            continue;
+        if let Some(record) = parse_line(line_num + 1, line_offset, line_bytes)? {
+            records.push(record);
         }
-        
-        // Find semicolon position
-        let semicolon_pos = memchr::memchr(b';', line_bytes)
-            .ok_or_else(|| WeatherError::InvalidFormat(
-                format!("Line {}: No semicolon delimiter found", line_num + 1)
-            ))?;
-            
-        if semicolon_pos == 0 {
-            return Err(WeatherError::InvalidFormat(
-                format!("Line {}: Weather station name cannot be empty", line_num + 1)
-            ));
-        }
-        
-        // Extract station name (trim whitespace)
-        let station_bytes = &line_bytes[..semicolon_pos];
-        let station_str = std::str::from_utf8(station_bytes)
-            .map_err(|_| WeatherError::InvalidFormat(
-                format!("Line {}: Invalid UTF-8 in station name", line_num + 1)
-            ))?;
-        let station = station_str.trim().to_string();
-        
-        // Extract temperature (trim whitespace)
-        let temp_bytes = &line_bytes[semicolon_pos + 1..];
-        let temp_str = std::str::from_utf8(temp_bytes)
-            .map_err(|_| WeatherError::InvalidFormat(
-                format!("Line {}: Invalid UTF-8 in temperature", line_num + 1)
-            ))?;
-        let temperature_str = temp_str.trim();
-        let temperature = f64::from_str(temperature_str)
-            .map_err(|_| WeatherError::Parse(
-                format!("Line {}: Cannot parse temperature '{}' as a number", 
-                       line_num + 1, temperature_str)
-            ))?;
-
-        records.push(WeatherRecord::new(station, temperature));
+        line_offset += line_bytes.len() + 1;
     }
     
     Ok(records)
 }
 
-pub fn parse_records_unsafe(lines: &[&[u8]]) -> Result<Vec<WeatherRecord>, WeatherError> {
-    let mut records = Vec::with_capacity(lines.len());
+// Splits raw data itself so that line numbers and byte offsets of skipped lines are exact
+fn parse_data_with_policy<'a, T>(
+    data: &'a [u8],
+    policy: ErrorPolicy,
+    parse_line: LineParserFn<'a, T>,
+) -> Result<(Vec<T>, ErrorReport), WeatherError> {
+    let mut records = Vec::new();
+    let mut report = ErrorReport::new();
+    let mut position = 0;
+    let mut line_number = 0;
     
-    for (line_num, &line_bytes) in lines.iter().enumerate() {
-        // Skip empty lines
-        if line_bytes.is_empty() {
-            continue;
+    while let Some((line_offset, line_bytes)) = next_line(data, &mut position) {
+        line_number += 1;
+        match parse_line(line_number, line_offset, line_bytes) {
+            Ok(Some(record)) => records.push(record),
+            Ok(None) => {}
+            Err(error) => report.reject(policy, line_offset, line_bytes, error)?,
         }
-        
-        // Find semicolon position
-        let semicolon_pos = memchr::memchr(b';', line_bytes)
-            .ok_or_else(|| WeatherError::InvalidFormat(
-                format!("Line {}: No semicolon delimiter found", line_num + 1)
-            ))?;
-            
-        if semicolon_pos == 0 {
-            return Err(WeatherError::InvalidFormat(
-                format!("Line {}: Weather station name cannot be empty", line_num + 1)
-            ));
-        }
-        
-        // Extract station name (unsafe UTF-8 conversion - assumes valid UTF-8)
-        let station_bytes = &line_bytes[..semicolon_pos];
-        let station_str = unsafe { std::str::from_utf8_unchecked(station_bytes) };
-        let station = station_str.trim().to_string();
-        
-        // Extract temperature (unsafe UTF-8 conversion)
-        let temp_bytes = &line_bytes[semicolon_pos + 1..];
-        let temp_str = unsafe { std::str::from_utf8_unchecked(temp_bytes) };
-        let temperature_str = temp_str.trim();
-        let temperature = f64::from_str(temperature_str)
-            .map_err(|_| WeatherError::Parse(
-                format!("Line {}: Cannot parse temperature '{}' as a number", 
-                       line_num + 1, temperature_str)
-            ))?;
-
-        records.push(WeatherRecord::new(station, temperature));
     }
     
-    Ok(records)
+    Ok((records, report))
+}
+
+fn parse_line_string(line_number: usize, line_offset: usize, line_bytes: &[u8]) -> Result<Option<WeatherRecord>, ParseError> {
+    // Validate the whole line up front, then parse it without re-checking the station;
+    // an invalid line is parsed again with validation to report the exact error
+    let record = match std::str::from_utf8(line_bytes) {
+        Ok(line) => UNCHECKED_LINE_PARSER.parse(line_number, line_offset, line.as_bytes())?,
+        Err(_) => LINE_PARSER.parse(line_number, line_offset, line_bytes)?,
+    };
+    Ok(record.map(|record| record.to_record()))
+}
+
+fn parse_line_bytes(line_number: usize, line_offset: usize, line_bytes: &[u8]) -> Result<Option<WeatherRecord>, ParseError> {
+    // Only the station field is UTF-8 validated
+    let record = LINE_PARSER.parse(line_number, line_offset, line_bytes)?;
+    Ok(record.map(|record| record.to_record()))
+}
+
+// Only reachable through the `unsafe` parse_records_unsafe* functions below
+fn parse_line_unsafe(line_number: usize, line_offset: usize, line_bytes: &[u8]) -> Result<Option<WeatherRecord>, ParseError> {
+    // Station names are not UTF-8 validated
+    let record = UNCHECKED_LINE_PARSER.parse(line_number, line_offset, line_bytes)?;
+    Ok(record.map(|record| record.to_record()))
+}
+
+fn parse_line_bytes_ref(line_number: usize, line_offset: usize, line_bytes: &[u8]) -> Result<Option<WeatherRecordRef<'_>>, ParseError> {
+    // Borrow station name (validated, not copied)
+    let record = LINE_PARSER.parse(line_number, line_offset, line_bytes)?;
+    Ok(record.map(|record| WeatherRecordRef::new(record.station.as_bytes(), record.temperature)))
+}
+
+// Only reachable through the `unsafe` parse_records_unsafe_ref* functions below
+fn parse_line_unsafe_ref(line_number: usize, line_offset: usize, line_bytes: &[u8]) -> Result<Option<WeatherRecordRef<'_>>, ParseError> {
+    // Borrow station name (no UTF-8 validation)
+    let record = UNCHECKED_LINE_PARSER.parse(line_number, line_offset, line_bytes)?;
+    Ok(record.map(|record| WeatherRecordRef::new(record.station.as_bytes(), record.temperature)))
+}
+
+fn parse_line_fixed(line_number: usize, line_offset: usize, line_bytes: &[u8]) -> Result<Option<WeatherRecordFixed>, ParseError> {
+    // Temperatures must have the 1BRC shape to fit in fixed-point tenths
+    let record = LINE_PARSER.parse_fixed(line_number, line_offset, line_bytes)?;
+    Ok(record.map(|(station, temperature)| WeatherRecordFixed::new(station.to_string(), temperature)))
+}
+
+pub fn parse_records_string(lines: &[&[u8]]) -> Result<Vec<WeatherRecord>, WeatherError> {
+    parse_lines(lines, parse_line_string)
+}
+
+pub fn parse_records_bytes(lines: &[&[u8]]) -> Result<Vec<WeatherRecord>, WeatherError> {
+    parse_lines(lines, parse_line_bytes)
+}
+
+/// Skips UTF-8 validation of station names.
+///
+/// # Safety
+///
+/// Every line must be valid UTF-8.
+pub unsafe fn parse_records_unsafe(lines: &[&[u8]]) -> Result<Vec<WeatherRecord>, WeatherError> {
+    parse_lines(lines, parse_line_unsafe)
+}
+
+pub fn parse_records_bytes_ref<'a>(lines: &[&'a [u8]]) -> Result<Vec<WeatherRecordRef<'a>>, WeatherError> {
+    parse_lines(lines, parse_line_bytes_ref)
+}
+
+/// Skips UTF-8 validation of station names.
+///
+/// # Safety
+///
+/// Every line must be valid UTF-8.
+pub unsafe fn parse_records_unsafe_ref<'a>(lines: &[&'a [u8]]) -> Result<Vec<WeatherRecordRef<'a>>, WeatherError> {
+    parse_lines(lines, parse_line_unsafe_ref)
+}
+
+pub fn parse_records_fixed(lines: &[&[u8]]) -> Result<Vec<WeatherRecordFixed>, WeatherError> {
+    parse_lines(lines, parse_line_fixed)
+}
+
+// Policy-aware variants take the raw buffer instead of split lines so skipped lines
+// can be reported with their real line number and byte offset
+pub fn parse_records_string_with_policy(data: &[u8], policy: ErrorPolicy) -> Result<(Vec<WeatherRecord>, ErrorReport), WeatherError> {
+    parse_data_with_policy(data, policy, parse_line_string)
+}
+
+pub fn parse_records_bytes_with_policy(data: &[u8], policy: ErrorPolicy) -> Result<(Vec<WeatherRecord>, ErrorReport), WeatherError> {
+    parse_data_with_policy(data, policy, parse_line_bytes)
+}
+
+/// Skips UTF-8 validation of station names.
+///
+/// # Safety
+///
+/// `data` must be valid UTF-8.
+pub unsafe fn parse_records_unsafe_with_policy(data: &[u8], policy: ErrorPolicy) -> Result<(Vec<WeatherRecord>, ErrorReport), WeatherError> {
+    parse_data_with_policy(data, policy, parse_line_unsafe)
+}
+
+pub fn parse_records_bytes_ref_with_policy(data: &[u8], policy: ErrorPolicy) -> Result<(Vec<WeatherRecordRef<'_>>, ErrorReport), WeatherError> {
+    parse_data_with_policy(data, policy, parse_line_bytes_ref)
+}
+
+/// Skips UTF-8 validation of station names.
+///
+/// # Safety
+///
+/// `data` must be valid UTF-8.
+pub unsafe fn parse_records_unsafe_ref_with_policy(data: &[u8], policy: ErrorPolicy) -> Result<(Vec<WeatherRecordRef<'_>>, ErrorReport), WeatherError> {
+    parse_data_with_policy(data, policy, parse_line_unsafe_ref)
+}
+
+pub fn parse_records_fixed_with_policy(data: &[u8], policy: ErrorPolicy) -> Result<(Vec<WeatherRecordFixed>, ErrorReport), WeatherError> {
+    parse_data_with_policy(data, policy, parse_line_fixed)
 }
 
 // ============================================================================
@@ -216,11 +282,30 @@ pub fn aggregate_records_std(records: &[WeatherRecord]) -> HashTable<String, Sta
     let mut station_stats: HashTable<String, StationStats> = HashTable::new();
     
     for record in records {
-        match station_stats.get(&record.station) {
-            Some(existing_stats) => {
-                let mut updated_stats = existing_stats.clone();
-                updated_stats.add_temperature(record.temperature);
-                station_stats.insert(record.station.clone(), updated_stats);
+        match station_stats.get_mut(&record.station) {
+            Some(stats) => {
+                stats.add_temperature(record.temperature);
+            }
+            None => {
+                let new_stats = StationStats::new(record.station.clone(), record.temperature);
+                station_stats.insert(record.station.clone(), new_stats);
+            }
+        }
+    }
+    
+    station_stats
+}
+
+pub fn aggregate_records_with_hasher<S>(records: &[WeatherRecord]) -> HashTable<String, StationStats, S>
+where
+    S: BuildHasher + Default,
+{
+    let mut station_stats: HashTable<String, StationStats, S> = HashTable::default();
+    
+    for record in records {
+        match station_stats.get_mut(&record.station) {
+            Some(stats) => {
+                stats.add_temperature(record.temperature);
             }
             None => {
                 let new_stats = StationStats::new(record.station.clone(), record.temperature);
@@ -250,6 +335,98 @@ pub fn aggregate_records_fx(records: &[WeatherRecord]) -> FxHashMap<String, Stat
     station_stats
 }
 
+pub fn aggregate_records_ref(records: &[WeatherRecordRef<'_>]) -> HashTable<String, StationStats> {
+    let mut station_stats: HashTable<String, StationStats> = HashTable::new();
+    
+    for record in records {
+        // Probe with the borrowed bytes so the station name is only copied on first insert
+        match station_stats.get_bytes_mut(record.station) {
+            Some(stats) => {
+                stats.add_temperature(record.temperature);
+            }
+            None => {
+                let station_name = String::from_utf8_lossy(record.station).into_owned();
+                station_stats.insert(station_name.clone(), StationStats::new(station_name, record.temperature));
+            }
+        }
+    }
+    
+    station_stats
+}
+
+pub fn aggregate_records_byte_table<S>(records: &[WeatherRecordRef<'_>]) -> HashTable<String, StationStats>
+where
+    S: BytesHasher + Default,
+{
+    // Station bytes are hashed directly in one call; the stored hash is reused on growth
+    let mut byte_stats: ByteHashTable<StationStats, S> = ByteHashTable::default();
+    
+    for record in records {
+        // A new station starts from this record's temperature, so only existing ones add it
+        let mut inserted = false;
+        let stats = byte_stats.get_or_insert_with(record.station, || {
+            inserted = true;
+            StationStats::new(String::from_utf8_lossy(record.station).into_owned(), record.temperature)
+        });
+        if !inserted {
+            stats.add_temperature(record.temperature);
+        }
+    }
+    
+    into_string_keyed(byte_stats)
+}
+
+pub fn aggregate_records_fused(data: &[u8]) -> Result<HashTable<String, StationStats>, WeatherError> {
+    let mut byte_stats: ByteHashTable<StationStats, FusedHashBuilder> = ByteHashTable::default();
+    
+    for record in FusedScanner::new(data) {
+        let record = record?;
+        
+        // The scanner already hashed the station, so the lookup skips hashing entirely
+        match byte_stats.get_mut_with_hash(record.station_hash, record.station.as_bytes()) {
+            Some(stats) => {
+                stats.add_temperature(record.temperature);
+            }
+            None => {
+                let stats = StationStats::new(record.station.to_string(), record.temperature);
+                byte_stats.insert_with_hash(record.station_hash, record.station.as_bytes(), stats);
+            }
+        }
+    }
+    
+    Ok(into_string_keyed(byte_stats))
+}
+
+// Moves each entry into a String-keyed table, reusing the key's allocation when it is UTF-8
+fn into_string_keyed<S: BytesHasher>(byte_stats: ByteHashTable<StationStats, S>) -> HashTable<String, StationStats> {
+    let mut station_stats: HashTable<String, StationStats> = HashTable::with_capacity(byte_stats.len());
+    for (key, stats) in byte_stats {
+        let station = String::from_utf8(key.into_vec())
+            .unwrap_or_else(|error| String::from_utf8_lossy(error.as_bytes()).into_owned());
+        station_stats.insert(station, stats);
+    }
+    
+    station_stats
+}
+
+pub fn aggregate_records_fixed(records: &[WeatherRecordFixed]) -> HashTable<String, StationStatsFixed> {
+    let mut station_stats: HashTable<String, StationStatsFixed> = HashTable::new();
+    
+    for record in records {
+        match station_stats.get_mut(&record.station) {
+            Some(stats) => {
+                stats.add_temperature(record.temperature);
+            }
+            None => {
+                let new_stats = StationStatsFixed::new(record.station.clone(), record.temperature);
+                station_stats.insert(record.station.clone(), new_stats);
+            }
+        }
+    }
+    
+    station_stats
+}
+
 pub fn aggregate_records_streaming<I>(records: I) -> HashTable<String, StationStats> 
 where 
     I: Iterator<Item = WeatherRecord>
@@ -257,97 +434,606 @@ where
     let mut station_stats: HashTable<String, StationStats> = HashTable::new();
     
     for record in records {
-        match station_stats.get(&record.station) {
+        station_stats
+            .entry(record.station)
+            .and_modify(|stats| stats.add_temperature(record.temperature))
+            .or_insert_with_key(|station| StationStats::new(station.clone(), record.temperature));
+    }
+    
+    station_stats
+}
+
+pub fn merge_station_stats<A: StationAccumulator>(target: &mut HashTable<String, A>, source: HashTable<String, A>) {
+    // Stations new to `target` are moved over rather than cloned
+    for (station, stats) in source {
+        match target.entry(station) {
+            Entry::Occupied(mut entry) => entry.get_mut().merge(&stats),
+            Entry::Vacant(entry) => {
+                entry.insert(stats);
+            }
+        }
+    }
+}
+
+// Same as merge_station_stats, but leaves `source` intact (e.g. for per-file breakdowns)
+pub fn merge_station_stats_ref<A: StationAccumulator>(target: &mut HashTable<String, A>, source: &HashTable<String, A>) {
+    for (station, stats) in source.iter() {
+        match target.get_mut(station) {
             Some(existing_stats) => {
-                let mut updated_stats = existing_stats.clone();
-                updated_stats.add_temperature(record.temperature);
-                station_stats.insert(record.station.clone(), updated_stats);
+                existing_stats.merge(stats);
             }
             None => {
-                let new_stats = StationStats::new(record.station.clone(), record.temperature);
-                station_stats.insert(record.station.clone(), new_stats);
+                target.insert(station.clone(), stats.clone());
             }
         }
     }
-    
-    station_stats
 }
 
 // ============================================================================
 // Stage 5: Full Pipeline Variants
 // ============================================================================
 
-pub fn pipeline_current(file_path: &str) -> Result<HashTable<String, StationStats>, Box<dyn std::error::Error>> {
+pub fn pipeline_current(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
     // Use existing implementation
     crate::process_weather_file_silent(file_path)
 }
 
-pub fn pipeline_mmap_string(file_path: &str) -> Result<HashTable<String, StationStats>, Box<dyn std::error::Error>> {
+pub fn pipeline_mmap_string(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
     let mmap = read_file_raw_mmap(file_path)?;
     let lines = split_into_lines_simd(&mmap);
     let records = parse_records_string(&lines)?;
     Ok(aggregate_records_std(&records))
 }
 
-pub fn pipeline_mmap_bytes(file_path: &str) -> Result<HashTable<String, StationStats>, Box<dyn std::error::Error>> {
+pub fn pipeline_mmap_bytes(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
     let mmap = read_file_raw_mmap(file_path)?;
     let lines = split_into_lines_simd(&mmap);
     let records = parse_records_bytes(&lines)?;
     Ok(aggregate_records_std(&records))
 }
 
-pub fn pipeline_mmap_unsafe(file_path: &str) -> Result<HashTable<String, StationStats>, Box<dyn std::error::Error>> {
+/// # Safety
+///
+/// The file must be valid UTF-8.
+pub unsafe fn pipeline_mmap_unsafe(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
     let mmap = read_file_raw_mmap(file_path)?;
     let lines = split_into_lines_simd(&mmap);
-    let records = parse_records_unsafe(&lines)?;
+    // SAFETY: the caller guarantees the file is valid UTF-8
+    let records = unsafe { parse_records_unsafe(&lines)? };
     Ok(aggregate_records_std(&records))
 }
 
-pub fn pipeline_buffered_bytes(file_path: &str) -> Result<HashTable<String, StationStats>, Box<dyn std::error::Error>> {
+pub fn pipeline_mmap_bytes_ref(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
+    let mmap = read_file_raw_mmap(file_path)?;
+    let lines = split_into_lines_simd(&mmap);
+    let records = parse_records_bytes_ref(&lines)?;
+    Ok(aggregate_records_ref(&records))
+}
+
+/// # Safety
+///
+/// The file must be valid UTF-8.
+pub unsafe fn pipeline_mmap_unsafe_ref(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
+    let mmap = read_file_raw_mmap(file_path)?;
+    let lines = split_into_lines_simd(&mmap);
+    // SAFETY: the caller guarantees the file is valid UTF-8
+    let records = unsafe { parse_records_unsafe_ref(&lines)? };
+    Ok(aggregate_records_ref(&records))
+}
+
+// The unchecked parsers behind one UTF-8 check of the whole file, so they are safe on any input.
+// A file that fails the check goes through the checked parser, which reports where it breaks.
+pub fn pipeline_mmap_unsafe_validated(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
+    let mmap = read_file_raw_mmap(file_path)?;
+    let lines = split_into_lines_simd(&mmap);
+    let records = if std::str::from_utf8(&mmap).is_ok() {
+        // SAFETY: the whole file, and so every line in it, was just checked to be valid UTF-8
+        unsafe { parse_records_unsafe(&lines)? }
+    } else {
+        parse_records_bytes(&lines)?
+    };
+    Ok(aggregate_records_std(&records))
+}
+
+pub fn pipeline_mmap_unsafe_ref_validated(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
+    let mmap = read_file_raw_mmap(file_path)?;
+    let lines = split_into_lines_simd(&mmap);
+    let records = if std::str::from_utf8(&mmap).is_ok() {
+        // SAFETY: the whole file, and so every line in it, was just checked to be valid UTF-8
+        unsafe { parse_records_unsafe_ref(&lines)? }
+    } else {
+        parse_records_bytes_ref(&lines)?
+    };
+    Ok(aggregate_records_ref(&records))
+}
+
+pub fn pipeline_mmap_byte_table(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
+    let mmap = read_file_raw_mmap(file_path)?;
+    let lines = split_into_lines_simd(&mmap);
+    let records = parse_records_bytes_ref(&lines)?;
+    Ok(aggregate_records_byte_table::<crate::Xxh3_64Builder>(&records))
+}
+
+pub fn pipeline_mmap_fused(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
+    let mmap = read_file_raw_mmap(file_path)?;
+    aggregate_records_fused(&mmap)
+}
+
+pub fn pipeline_mmap_fixed(file_path: &str) -> Result<HashTable<String, StationStatsFixed>, WeatherError> {
+    let mmap = read_file_raw_mmap(file_path)?;
+    let lines = split_into_lines_simd(&mmap);
+    let records = parse_records_fixed(&lines)?;
+    Ok(aggregate_records_fixed(&records))
+}
+
+pub fn pipeline_buffered_bytes(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
     let data = read_file_raw_buffered(file_path)?;
     let lines = split_into_lines_simd(&data);
     let records = parse_records_bytes(&lines)?;
     Ok(aggregate_records_std(&records))
 }
 
-pub fn pipeline_streaming(file_path: &str) -> Result<HashTable<String, StationStats>, Box<dyn std::error::Error>> {
-    // Streaming version that doesn't load everything into memory
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
+pub fn pipeline_streaming(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
+    // Streaming version that doesn't load everything into memory or allocate per record
+    crate::WeatherCsvReader::from_path(file_path)?.aggregate()
+}
+
+pub fn pipeline_parallel(file_path: &str, threads: usize) -> Result<HashTable<String, StationStats>, WeatherError> {
+    let mmap = read_file_raw_mmap(file_path)?;
+    let data: &[u8] = &mmap;
+    let chunks = split_into_chunks(data, threads);
+    let chunk_starts = chunks.iter().scan(0, |start, chunk| {
+        let chunk_start = *start;
+        *start += chunk.len();
+        Some(chunk_start)
+    });
+    
+    let partial_results: Vec<Result<HashTable<String, StationStats>, WeatherError>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .iter()
+            .zip(chunk_starts)
+            .map(|(&chunk, chunk_start)| {
+                scope.spawn(move || {
+                    let lines = split_into_lines_simd(chunk);
+                    let records = parse_records_bytes(&lines).map_err(|error| locate_chunk_error(error, data, chunk_start))?;
+                    Ok(aggregate_records_std(&records))
+                })
+            })
+            .collect();
+        
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Aggregation thread panicked"))
+            .collect()
+    });
+    
     let mut station_stats: HashTable<String, StationStats> = HashTable::new();
+    for partial_result in partial_results {
+        merge_station_stats(&mut station_stats, partial_result?);
+    }
     
-    for (line_num, line_result) in reader.lines().enumerate() {
-        let line = line_result?;
-        let line = line.trim();
+    Ok(station_stats)
+}
+
+// A chunk is parsed as if it were the whole file, so its errors are moved to where the chunk
+// starts in `data`. The lines before it are only counted once something has failed.
+fn locate_chunk_error(error: WeatherError, data: &[u8], chunk_start: usize) -> WeatherError {
+    match error {
+        WeatherError::Parse(mut error) => {
+            error.line += memchr_iter(b'\n', &data[..chunk_start]).count();
+            error.byte_offset += chunk_start;
+            WeatherError::Parse(error)
+        }
+        error => error,
+    }
+}
+
+fn pipeline_parallel_all_cores(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
+    pipeline_parallel(file_path, std::thread::available_parallelism().map_or(1, |threads| threads.get()))
+}
+
+// ============================================================================
+// Pipeline Registry
+// ============================================================================
+
+pub type PipelineFn = fn(&str) -> Result<HashTable<String, StationStats>, WeatherError>;
+
+// A full pipeline that can be picked by name at runtime
+#[derive(Debug, Clone, Copy)]
+pub struct PipelineEntry {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub run: PipelineFn,
+}
+
+// Every pipeline producing StationStats that is safe on any input: pipeline_mmap_fixed only
+// takes 1BRC-shaped temperatures, and the mmap-unsafe entries run the *_unsafe variants only
+// after validating the whole file
+pub const PIPELINES: &[PipelineEntry] = &[
+    PipelineEntry { name: "current", description: "Buffered WeatherCsvReader (the default)", run: pipeline_current },
+    PipelineEntry { name: "mmap-string", description: "mmap, split lines, owned String records", run: pipeline_mmap_string },
+    PipelineEntry { name: "mmap-bytes", description: "mmap, split lines, parse from bytes", run: pipeline_mmap_bytes },
+    PipelineEntry { name: "mmap-unsafe", description: "mmap, one UTF-8 check, unchecked station names", run: pipeline_mmap_unsafe_validated },
+    PipelineEntry { name: "mmap-bytes-ref", description: "mmap, records borrow station names", run: pipeline_mmap_bytes_ref },
+    PipelineEntry { name: "mmap-unsafe-ref", description: "mmap, one UTF-8 check, borrowed unchecked names", run: pipeline_mmap_unsafe_ref_validated },
+    PipelineEntry { name: "mmap-byte-table", description: "mmap, byte-keyed table with stored hashes", run: pipeline_mmap_byte_table },
+    PipelineEntry { name: "mmap-fused", description: "mmap, fused SWAR scanner with rolling hash", run: pipeline_mmap_fused },
+    PipelineEntry { name: "buffered-bytes", description: "Whole file read into memory, parse from bytes", run: pipeline_buffered_bytes },
+    PipelineEntry { name: "streaming", description: "Chunked reader that never holds the whole file", run: pipeline_streaming },
+    PipelineEntry { name: "parallel", description: "mmap split into one chunk per core", run: pipeline_parallel_all_cores },
+];
+
+pub fn find_pipeline(name: &str) -> Option<&'static PipelineEntry> {
+    PIPELINES.iter().find(|pipeline| pipeline.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorKind, StationStatsExact};
+
+    const TEST_FILE: &str = "data/measurements_1KRecords.txt";
+
+    #[test]
+    fn test_split_into_chunks_on_line_boundaries() {
+        let data = b"a;1.0\nbb;2.0\nccc;3.0\ndddd;4.0\n";
         
-        if line.is_empty() {
-            continue;
+        for chunk_count in 1..=8 {
+            let chunks = split_into_chunks(data, chunk_count);
+            assert!(chunks.len() <= chunk_count);
+            assert_eq!(chunks.concat(), data.to_vec());
+            for chunk in &chunks {
+                assert_eq!(chunk.last(), Some(&b'\n'));
+            }
         }
+    }
 
-        let parts: Vec<&str> = line.split(';').collect();
-        if parts.len() != 2 {
-            return Err(Box::new(WeatherError::InvalidFormat(
-                format!("Line {} does not have exactly 2 columns", line_num + 1)
-            )));
+    #[test]
+    fn test_split_into_chunks_without_trailing_newline() {
+        let data = b"a;1.0\nb;2.0";
+        let chunks = split_into_chunks(data, 2);
+        assert_eq!(chunks.concat(), data.to_vec());
+        assert!(split_into_chunks(b"", 4).is_empty());
+    }
+
+    #[test]
+    fn test_aggregate_records_with_hasher_matches_std() {
+        let data = read_file_raw_buffered(TEST_FILE).unwrap();
+        let lines = split_into_lines_simd(&data);
+        let records = parse_records_bytes(&lines).unwrap();
+        let expected = aggregate_records_std(&records);
+        
+        let xxhash32 = aggregate_records_with_hasher::<crate::XxHash32Builder>(&records);
+        let fx = aggregate_records_with_hasher::<rustc_hash::FxBuildHasher>(&records);
+        
+        assert_eq!(xxhash32.len(), expected.len());
+        assert_eq!(fx.len(), expected.len());
+        for (station, expected_stats) in expected.iter() {
+            assert_eq!(xxhash32.get(station).unwrap().count, expected_stats.count);
+            assert_eq!(fx.get(station).unwrap().count, expected_stats.count);
         }
+    }
 
-        let station = parts[0].trim().to_string();
-        let temperature = f64::from_str(parts[1].trim())?;
+    #[test]
+    fn test_aggregate_records_byte_table_hashers_agree() {
+        let data = read_file_raw_buffered(TEST_FILE).unwrap();
+        let lines = split_into_lines_simd(&data);
+        let records = parse_records_bytes_ref(&lines).unwrap();
+        let xxhash64 = aggregate_records_byte_table::<crate::XxHash64Builder>(&records);
+        let xxh3 = aggregate_records_byte_table::<crate::Xxh3_64Builder>(&records);
         
-        let record = WeatherRecord::new(station, temperature);
+        assert_eq!(xxhash64.len(), xxh3.len());
+        for (station, stats) in xxhash64.iter() {
+            assert_eq!(xxh3.get(station).unwrap().count, stats.count);
+        }
+    }
+
+    #[test]
+    fn test_parse_records_bytes_ref_borrows_input() {
+        let data = b"Station1; 25.5\n  Station2  ;-10.2\n";
+        let lines = split_into_lines_simd(data);
+        let records = parse_records_bytes_ref(&lines).unwrap();
+        assert_eq!(records, vec![
+            WeatherRecordRef::new(b"Station1", 25.5),
+            WeatherRecordRef::new(b"Station2", -10.2),
+        ]);
         
-        match station_stats.get(&record.station) {
-            Some(existing_stats) => {
-                let mut updated_stats = existing_stats.clone();
-                updated_stats.add_temperature(record.temperature);
-                station_stats.insert(record.station, updated_stats);
+        let data_range = data.as_ptr_range();
+        for record in &records {
+            assert!(data_range.contains(&record.station.as_ptr()));
+        }
+        
+        let lines: Vec<&[u8]> = vec![b"Station1;25.5", b"\xff\xfe;1.0"];
+        let error = parse_records_bytes_ref(&lines).unwrap_err();
+        assert_eq!(error.kind(), Some(ErrorKind::InvalidUtf8));
+        assert_eq!(error.line(), Some(2));
+    }
+
+    #[test]
+    fn test_aggregate_records_fused_locates_invalid_utf8() {
+        let data: &[u8] = b"Station1;25.5\nAb\xffc;1.0\n";
+        match aggregate_records_fused(data) {
+            Err(WeatherError::Parse(error)) => {
+                assert_eq!(error.kind, ErrorKind::InvalidUtf8);
+                assert_eq!((error.line, error.column, error.byte_offset), (2, 3, 16));
+                assert_eq!(error.bytes, b"\xff");
             }
-            None => {
-                let new_stats = StationStats::new(record.station.clone(), record.temperature);
-                station_stats.insert(record.station, new_stats);
+            _ => panic!("Expected Parse error"),
+        }
+    }
+
+    #[test]
+    fn test_pipeline_ref_variants_match_current() {
+        let expected = pipeline_current(TEST_FILE).unwrap();
+        
+        let ref_variants = [
+            pipeline_mmap_bytes_ref(TEST_FILE).unwrap(),
+            // SAFETY: the sample file is valid UTF-8
+            unsafe { pipeline_mmap_unsafe_ref(TEST_FILE) }.unwrap(),
+            pipeline_mmap_byte_table(TEST_FILE).unwrap(),
+            pipeline_mmap_fused(TEST_FILE).unwrap(),
+        ];
+        
+        for actual in ref_variants {
+            assert_eq!(actual.len(), expected.len());
+            
+            for (station, expected_stats) in expected.iter() {
+                let actual_stats = actual.get(station).unwrap();
+                assert_eq!(&actual_stats.station_name, station);
+                assert_eq!(actual_stats.count, expected_stats.count);
+                assert_eq!(actual_stats.min_temperature, expected_stats.min_temperature);
+                assert_eq!(actual_stats.max_temperature, expected_stats.max_temperature);
+                assert_eq!(actual_stats.sum_temperature, expected_stats.sum_temperature);
             }
         }
     }
-    
-    Ok(station_stats)
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_temperature_fixed() {
+        assert_eq!(parse_temperature_fixed(b"0.0"), Some(0));
+        assert_eq!(parse_temperature_fixed(b"1.5"), Some(15));
+        assert_eq!(parse_temperature_fixed(b"-1.5"), Some(-15));
+        assert_eq!(parse_temperature_fixed(b"-12.3"), Some(-123));
+        assert_eq!(parse_temperature_fixed(b"99.9"), Some(999));
+        assert_eq!(parse_temperature_fixed(b"-99.9"), Some(-999));
+        
+        assert_eq!(parse_temperature_fixed(b""), None);
+        assert_eq!(parse_temperature_fixed(b"-"), None);
+        assert_eq!(parse_temperature_fixed(b"12"), None);
+        assert_eq!(parse_temperature_fixed(b"12.34"), None);
+        assert_eq!(parse_temperature_fixed(b"123.4"), None);
+        assert_eq!(parse_temperature_fixed(b"1a.5"), None);
+        assert_eq!(parse_temperature_fixed(b"--1.5"), None);
+        assert_eq!(parse_temperature_fixed(b"invalid"), None);
+    }
+
+    #[test]
+    fn test_errors_after_blank_lines_keep_their_position() {
+        let data = b"\nStation1;25.5\n\n\r\nStation2;oops\n";
+        assert_eq!(split_into_lines_basic(data), split_into_lines_simd(data));
+        assert_eq!(split_into_lines_simd(data).len(), 5);
+        
+        match parse_records_bytes(&split_into_lines_simd(data)).unwrap_err() {
+            WeatherError::Parse(error) => {
+                assert_eq!(error.kind, ErrorKind::BadNumber);
+                assert_eq!((error.line, error.column, error.byte_offset), (5, 10, 27));
+                assert_eq!(&data[error.byte_offset..error.byte_offset + 4], b"oops");
+            }
+            other => panic!("Expected Parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_records_fixed() {
+        let lines: Vec<&[u8]> = vec![b"Station1; 25.5 ", b"", b"Station2;-10.2"];
+        let records = parse_records_fixed(&lines).unwrap();
+        assert_eq!(records, vec![
+            WeatherRecordFixed::new("Station1".to_string(), 255),
+            WeatherRecordFixed::new("Station2".to_string(), -102),
+        ]);
+        
+        let lines: Vec<&[u8]> = vec![b"Station1;25.5", b"Station2;invalid_temp"];
+        match parse_records_fixed(&lines).unwrap_err() {
+            WeatherError::Parse(error) => {
+                assert_eq!(error.kind, ErrorKind::BadNumber);
+                assert_eq!((error.line, error.column, error.byte_offset), (2, 10, 23));
+                assert_eq!(error.bytes, b"invalid_temp");
+            }
+            other => panic!("Expected Parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_records_with_policy() {
+        let data: &[u8] = b"Station1;25.5\n\nStation2 1.0\n;3.0\nStation3;abc\nStation4;-4.5";
+        
+        let (records, report) = parse_records_bytes_with_policy(data, ErrorPolicy::lenient()).unwrap();
+        assert_eq!(records.iter().map(|r| r.station.as_str()).collect::<Vec<_>>(), vec!["Station1", "Station4"]);
+        assert_eq!(report.skipped().iter().map(|line| (line.line_number, line.byte_offset, line.kind)).collect::<Vec<_>>(), vec![
+            (3, 15, ErrorKind::MissingDelimiter),
+            (4, 28, ErrorKind::EmptyStation),
+            (5, 33, ErrorKind::BadNumber),
+        ]);
+        assert_eq!(report.skipped()[2].content, b"Station3;abc");
+        
+        // Every parser agrees on which lines are skipped
+        let expected: Vec<_> = report.skipped().iter().map(|line| (line.line_number, line.kind)).collect();
+        let reports = [
+            parse_records_string_with_policy(data, ErrorPolicy::lenient()).unwrap().1,
+            // SAFETY: `data` is ASCII
+            unsafe { parse_records_unsafe_with_policy(data, ErrorPolicy::lenient()) }.unwrap().1,
+            parse_records_bytes_ref_with_policy(data, ErrorPolicy::lenient()).unwrap().1,
+            unsafe { parse_records_unsafe_ref_with_policy(data, ErrorPolicy::lenient()) }.unwrap().1,
+            parse_records_fixed_with_policy(data, ErrorPolicy::lenient()).unwrap().1,
+        ];
+        for report in reports {
+            let actual: Vec<_> = report.skipped().iter().map(|line| (line.line_number, line.kind)).collect();
+            assert_eq!(actual, expected);
+        }
+        
+        assert!(matches!(
+            parse_records_bytes_with_policy(data, ErrorPolicy::Strict),
+            Err(WeatherError::Parse(ParseError { kind: ErrorKind::MissingDelimiter, line: 3, .. }))
+        ));
+        assert!(matches!(
+            parse_records_fixed_with_policy(data, ErrorPolicy::lenient_with_limit(2)),
+            Err(WeatherError::TooManyErrors(2))
+        ));
+    }
+
+    #[test]
+    fn test_parse_records_with_policy_matches_strict_on_clean_data() {
+        let data = read_file_raw_buffered(TEST_FILE).unwrap();
+        let lines = split_into_lines_simd(&data);
+        
+        let (records, report) = parse_records_bytes_with_policy(&data, ErrorPolicy::lenient()).unwrap();
+        assert!(report.is_empty());
+        assert_eq!(records, parse_records_bytes(&lines).unwrap());
+    }
+
+    #[test]
+    fn test_pipeline_mmap_fixed_matches_current() {
+        let expected = pipeline_current(TEST_FILE).unwrap();
+        let actual = pipeline_mmap_fixed(TEST_FILE).unwrap();
+        assert_eq!(actual.len(), expected.len());
+        
+        for (station, expected_stats) in expected.iter() {
+            let actual_stats = actual.get(station).unwrap();
+            assert_eq!(actual_stats.count as usize, expected_stats.count);
+            assert_eq!(actual_stats.min_temperature(), expected_stats.min_temperature);
+            assert_eq!(actual_stats.max_temperature(), expected_stats.max_temperature);
+            assert!((actual_stats.avg_temperature() - expected_stats.avg_temperature()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_pipeline_parallel_matches_current() {
+        let expected = pipeline_current(TEST_FILE).unwrap();
+        
+        for threads in [1, 2, 3, 16] {
+            let actual = pipeline_parallel(TEST_FILE, threads).unwrap();
+            assert_eq!(actual.len(), expected.len());
+            
+            for (station, expected_stats) in expected.iter() {
+                let actual_stats = actual.get(station).unwrap();
+                assert_eq!(actual_stats.count, expected_stats.count);
+                assert_eq!(actual_stats.min_temperature, expected_stats.min_temperature);
+                assert_eq!(actual_stats.max_temperature, expected_stats.max_temperature);
+                assert!((actual_stats.sum_temperature - expected_stats.sum_temperature).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pipeline_parallel_locates_errors_in_later_chunks() {
+        let mut data = Vec::new();
+        for i in 0..200 {
+            data.extend_from_slice(format!("Station{};{}.5\n", i % 7, i % 50).as_bytes());
+            if i % 30 == 0 {
+                data.extend_from_slice(b"\n");
+            }
+        }
+        let bad_offset = data.len();
+        data.extend_from_slice(b"Station3;1.0;2.0\nStation4;3.0\n");
+        let bad_line = data[..bad_offset].iter().filter(|&&byte| byte == b'\n').count() + 1;
+        
+        let path = std::env::temp_dir().join(format!("obr_parallel_error_{}.txt", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+        let path = path.to_str().unwrap();
+        
+        let expected = match pipeline_mmap_bytes(path) {
+            Err(WeatherError::Parse(error)) => error,
+            other => panic!("Expected Parse error, got {:?}", other.err()),
+        };
+        assert_eq!(expected.line, bad_line);
+        assert!(expected.byte_offset >= bad_offset && expected.byte_offset < bad_offset + 16);
+        
+        for threads in [1, 2, 4, 7] {
+            match pipeline_parallel(path, threads) {
+                Err(WeatherError::Parse(error)) => {
+                    assert_eq!((error.kind, error.line, error.column, error.byte_offset), (expected.kind, expected.line, expected.column, expected.byte_offset), "{} threads", threads);
+                }
+                other => panic!("Expected Parse error, got {:?}", other.err()),
+            }
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    // Deterministic Fisher–Yates shuffle (xorshift64) so failures are reproducible
+    fn shuffled<T: Clone>(items: &[T], mut seed: u64) -> Vec<T> {
+        let mut items = items.to_vec();
+        for i in (1..items.len()).rev() {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            items.swap(i, (seed % (i as u64 + 1)) as usize);
+        }
+        items
+    }
+
+    fn aggregate_exact(records: &[WeatherRecord]) -> HashTable<String, StationStatsExact> {
+        let mut station_stats: HashTable<String, StationStatsExact> = HashTable::new();
+        for record in records {
+            match station_stats.entry(record.station.clone()) {
+                Entry::Occupied(mut entry) => entry.get_mut().add_temperature(record.temperature),
+                Entry::Vacant(entry) => {
+                    entry.insert(StationStatsExact::new(record.station.clone(), record.temperature));
+                }
+            }
+        }
+        station_stats
+    }
+
+    // StationStatsExact sums whole tenths, so this holds for any order, not just for this data;
+    // a plain f64 sum already fails it
+    #[test]
+    fn test_averages_ignore_record_order() {
+        let data = read_file_raw_buffered(TEST_FILE).unwrap();
+        let records = parse_records_bytes(&split_into_lines_simd(&data)).unwrap();
+        
+        // Pool every temperature into one station as well, so the sums have 1000 terms
+        let pooled = |records: &[WeatherRecord]| -> Vec<WeatherRecord> {
+            records.iter().map(|record| WeatherRecord::new("All".to_string(), record.temperature)).collect()
+        };
+        let expected = aggregate_exact(&records);
+        let expected_all = aggregate_exact(&pooled(&records)).get("All").unwrap().stats.avg_temperature();
+        assert!(expected.values().all(|stats| stats.sum_remainder == 0.0));
+        
+        for seed in [1, 42, 0x9E37_79B9_7F4A_7C15] {
+            let shuffled_records = shuffled(&records, seed);
+            
+            let actual = aggregate_exact(&shuffled_records);
+            assert_eq!(actual.len(), expected.len());
+            for (station, expected_stats) in expected.iter() {
+                assert_eq!(actual.get(station).unwrap().stats.avg_temperature(), expected_stats.stats.avg_temperature(), "{}", station);
+            }
+            
+            let all = aggregate_exact(&pooled(&shuffled_records));
+            assert_eq!(all.get("All").unwrap().stats.avg_temperature(), expected_all);
+            
+            // Chunked aggregation merged in any order must agree too
+            let mut merged: HashTable<String, StationStatsExact> = HashTable::new();
+            for chunk in pooled(&shuffled_records).chunks(97).rev() {
+                merge_station_stats(&mut merged, aggregate_exact(chunk));
+            }
+            assert_eq!(merged.get("All").unwrap().stats.avg_temperature(), expected_all);
+        }
+    }
+
+    #[test]
+    fn test_pipeline_registry() {
+        let mut names: Vec<&str> = PIPELINES.iter().map(|pipeline| pipeline.name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), PIPELINES.len());
+        
+        let expected = pipeline_current(TEST_FILE).unwrap();
+        for pipeline in PIPELINES {
+            let actual = (pipeline.run)(TEST_FILE).unwrap();
+            assert_eq!(actual.len(), expected.len(), "{}", pipeline.name);
+        }
+        assert_eq!(find_pipeline("mmap-fused").unwrap().name, "mmap-fused");
+        assert!(find_pipeline("mmap-fixed").is_none());
+        assert_eq!(find_pipeline("mmap-unsafe").unwrap().name, "mmap-unsafe");
+    }
+}
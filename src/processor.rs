@@ -1,88 +1,129 @@
-use crate::{WeatherCsvReader, MmapWeatherCsvReader, HashTable, StationStats};
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use crate::{WeatherCsvReader, MmapWeatherCsvReader, WeatherInput, RecordSource, HashTable, StationStats, StationAccumulator, StationHistogramStats, StationVarianceStats, ErrorPolicy, ErrorReport, WeatherError, PipelineEntry, merge_station_stats, merge_station_stats_ref, STDIN_PATH};
 
-pub fn read_weather_file(file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut reader = WeatherCsvReader::from_path(file_path)?;
-    let mut station_stats: HashTable<String, StationStats> = HashTable::new();
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Csv,
+    Challenge,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
     
-    for record_result in reader.records() {
-        let record = record_result?;
-        
-        match station_stats.get(&record.station) {
-            Some(existing_stats) => {
-                let mut updated_stats = existing_stats.clone();
-                updated_stats.add_temperature(record.temperature);
-                station_stats.insert(record.station, updated_stats);
-            }
-            None => {
-                let new_stats = StationStats::new(record.station.clone(), record.temperature);
-                station_stats.insert(record.station, new_stats);
-            }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(OutputFormat::Csv),
+            "challenge" => Ok(OutputFormat::Challenge),
+            _ => Err(format!("Unknown output format '{}' (expected 'csv' or 'challenge')", s)),
         }
     }
+}
+
+// What read_weather_file* print besides the per-station min/mean/max
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutputOptions {
+    pub format: OutputFormat,
+    // Adds population variance and standard deviation columns; needs an accumulator that
+    // tracks moments (StationVarianceStats)
+    pub variance: bool,
+    // Adds one column per percentile (0..=100); needs histogram-backed stats
+    pub percentiles: Vec<f64>,
+    // Multi-file runs also print each file's table
+    pub per_file: bool,
+}
+
+impl OutputOptions {
+    pub fn new(format: OutputFormat) -> Self {
+        Self { format, ..Self::default() }
+    }
+}
+
+pub fn read_weather_file(file_path: &str) -> Result<(), WeatherError> {
+    read_weather_file_with_format(file_path, OutputFormat::Csv)
+}
+
+pub fn read_weather_file_with_format(file_path: &str, format: OutputFormat) -> Result<(), WeatherError> {
+    read_weather_file_with_policy(file_path, format, ErrorPolicy::Strict).map(|_| ())
+}
+
+pub fn read_weather_file_with_policy(file_path: &str, format: OutputFormat, policy: ErrorPolicy) -> Result<ErrorReport, WeatherError> {
+    read_weather_file_with_options(file_path, &OutputOptions::new(format), policy)
+}
+
+// Returns the lines skipped under a lenient policy so the caller can report them.
+// `file_path` may be "-" for stdin; regular files are memory-mapped, anything else is streamed.
+pub fn read_weather_file_with_options(file_path: &str, options: &OutputOptions, policy: ErrorPolicy) -> Result<ErrorReport, WeatherError> {
+    // Only pay for moments and histograms when their columns are printed
+    match (options.variance, options.percentiles.is_empty()) {
+        (false, true) => read_weather_file_with::<StationStats>(file_path, options, policy),
+        (true, true) => read_weather_file_with::<StationVarianceStats>(file_path, options, policy),
+        (false, false) => read_weather_file_with::<StationHistogramStats>(file_path, options, policy),
+        (true, false) => read_weather_file_with::<StationHistogramStats<StationVarianceStats>>(file_path, options, policy),
+    }
+}
+
+// Runs one of the registered pipelines (see `PIPELINES`) on a single file and prints its table.
+// Pipelines are strict and keep plain StationStats, so variance and percentiles aren't available here.
+pub fn read_weather_file_with_pipeline(file_path: &str, pipeline: &PipelineEntry, options: &OutputOptions) -> Result<(), WeatherError> {
+    let station_stats = (pipeline.run)(file_path)?;
     
     if station_stats.is_empty() {
         eprintln!("No weather records found in the file.");
         return Ok(());
     }
     
-    // Output CSV headers
-    println!("Station,Records,MinTemperature,MaxTemperature,AvgTemperature");
+    print_station_stats(&station_stats, options);
+    Ok(())
+}
+
+fn read_weather_file_with<A: StationAccumulator>(file_path: &str, options: &OutputOptions, policy: ErrorPolicy) -> Result<ErrorReport, WeatherError> {
+    let (station_stats, error_report) = process_weather_file_silent_with::<A>(file_path, policy)?;
     
-    // Output each station's statistics in CSV format
-    for (station, stats) in station_stats.iter() {
-        println!("{},{},{:.1},{:.1},{:.1}",
-                 station,
-                 stats.count,
-                 stats.min_temperature,
-                 stats.max_temperature,
-                 stats.avg_temperature());
+    if station_stats.is_empty() {
+        eprintln!("No weather records found in the file.");
+        return Ok(error_report);
     }
     
-    Ok(())
+    print_station_stats(&station_stats, options);
+    
+    Ok(error_report)
 }
 
 #[inline]
-pub fn process_weather_file_silent(file_path: &str) -> Result<HashTable<String, StationStats>, Box<dyn std::error::Error>> {
+pub fn process_weather_file_silent(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
     let mut reader = WeatherCsvReader::from_path(file_path)?;
     let mut station_stats: HashTable<String, StationStats> = HashTable::new();
     
     for record_result in reader.records() {
         let record = record_result?;
         
-        match station_stats.get(&record.station) {
-            Some(existing_stats) => {
-                let mut updated_stats = existing_stats.clone();
-                updated_stats.add_temperature(record.temperature);
-                station_stats.insert(record.station, updated_stats);
-            }
-            None => {
-                let new_stats = StationStats::new(record.station.clone(), record.temperature);
-                station_stats.insert(record.station, new_stats);
-            }
-        }
+        station_stats
+            .entry(record.station)
+            .and_modify(|stats| stats.add_temperature(record.temperature))
+            .or_insert_with_key(|station| StationStats::new(station.clone(), record.temperature));
     }
     
     Ok(station_stats)
 }
 
-pub fn read_weather_file_mmap(file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+pub fn read_weather_file_mmap(file_path: &str) -> Result<(), WeatherError> {
+    read_weather_file_mmap_with_format(file_path, OutputFormat::Csv)
+}
+
+pub fn read_weather_file_mmap_with_format(file_path: &str, format: OutputFormat) -> Result<(), WeatherError> {
     let mut reader = MmapWeatherCsvReader::from_path(file_path)?;
     let mut station_stats: HashTable<String, StationStats> = HashTable::new();
     
     for record_result in reader.records() {
         let record = record_result?;
         
-        match station_stats.get(&record.station) {
-            Some(existing_stats) => {
-                let mut updated_stats = existing_stats.clone();
-                updated_stats.add_temperature(record.temperature);
-                station_stats.insert(record.station, updated_stats);
-            }
-            None => {
-                let new_stats = StationStats::new(record.station.clone(), record.temperature);
-                station_stats.insert(record.station, new_stats);
-            }
-        }
+        station_stats
+            .entry(record.station)
+            .and_modify(|stats| stats.add_temperature(record.temperature))
+            .or_insert_with_key(|station| StationStats::new(station.clone(), record.temperature));
     }
     
     if station_stats.is_empty() {
@@ -90,42 +131,478 @@ pub fn read_weather_file_mmap(file_path: &str) -> Result<(), Box<dyn std::error:
         return Ok(());
     }
     
-    // Output CSV headers
-    println!("Station,Records,MinTemperature,MaxTemperature,AvgTemperature");
-    
-    // Output each station's statistics in CSV format
-    for (station, stats) in station_stats.iter() {
-        println!("{},{},{:.1},{:.1},{:.1}",
-                 station,
-                 stats.count,
-                 stats.min_temperature,
-                 stats.max_temperature,
-                 stats.avg_temperature());
-    }
+    print_station_stats(&station_stats, &OutputOptions::new(format));
     
     Ok(())
 }
 
 #[inline]
-pub fn process_weather_file_silent_mmap(file_path: &str) -> Result<HashTable<String, StationStats>, Box<dyn std::error::Error>> {
+pub fn process_weather_file_silent_mmap(file_path: &str) -> Result<HashTable<String, StationStats>, WeatherError> {
     let mut reader = MmapWeatherCsvReader::from_path(file_path)?;
     let mut station_stats: HashTable<String, StationStats> = HashTable::new();
     
     for record_result in reader.records() {
         let record = record_result?;
         
-        match station_stats.get(&record.station) {
-            Some(existing_stats) => {
-                let mut updated_stats = existing_stats.clone();
-                updated_stats.add_temperature(record.temperature);
-                station_stats.insert(record.station, updated_stats);
+        station_stats
+            .entry(record.station)
+            .and_modify(|stats| stats.add_temperature(record.temperature))
+            .or_insert_with_key(|station| StationStats::new(station.clone(), record.temperature));
+    }
+    
+    Ok(station_stats)
+}
+
+// Like process_weather_file_silent_mmap for any accumulator and error policy. `file_path` may
+// be "-" for stdin; regular files are memory-mapped, anything else is streamed.
+pub fn process_weather_file_silent_with<A: StationAccumulator>(file_path: &str, policy: ErrorPolicy) -> Result<(HashTable<String, A>, ErrorReport), WeatherError> {
+    let mut reader = WeatherInput::open(file_path)?.with_error_policy(policy);
+    let station_stats = reader.aggregate_with()?;
+    
+    Ok((station_stats, reader.into_error_report()))
+}
+
+// Results for one input file of a multi-file run
+pub struct FileStationStats<A = StationStats> {
+    pub path: String,
+    // Only kept when the run was asked for per-file tables
+    pub station_stats: Option<HashTable<String, A>>,
+    pub error_report: ErrorReport,
+}
+
+// Per-file results (in input order) plus all of them merged into one table
+pub struct MultiFileStationStats<A = StationStats> {
+    pub files: Vec<FileStationStats<A>>,
+    pub station_stats: HashTable<String, A>,
+}
+
+impl<A> MultiFileStationStats<A> {
+    pub fn skipped_lines(&self) -> usize {
+        self.files.iter().map(|file| file.error_report.len()).sum()
+    }
+}
+
+pub fn process_weather_files_silent<S: AsRef<str> + Sync>(
+    file_paths: &[S],
+    policy: ErrorPolicy,
+    threads: usize,
+    per_file: bool,
+) -> Result<MultiFileStationStats, WeatherError> {
+    process_weather_files_silent_with(file_paths, policy, threads, per_file)
+}
+
+// Aggregates the files on up to `threads` threads. Tables are merged into the total in input
+// order whichever thread finishes first, so float sums come out the same on every run; without
+// `per_file` each one is dropped once merged, with it every file's table is kept as well.
+// `policy` applies per file, errors are tagged with the path they came from, and no new file is
+// started once one has failed.
+pub fn process_weather_files_silent_with<A: StationAccumulator, S: AsRef<str> + Sync>(
+    file_paths: &[S],
+    policy: ErrorPolicy,
+    threads: usize,
+    per_file: bool,
+) -> Result<MultiFileStationStats<A>, WeatherError> {
+    // A second "-" would find stdin already drained
+    if file_paths.iter().filter(|path| path.as_ref() == STDIN_PATH).count() > 1 {
+        let message = format!("'{}' (stdin) can only be given once", STDIN_PATH);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+    }
+    
+    let next_file = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let merged: Mutex<OrderedMerge<A>> = Mutex::new(OrderedMerge::new(file_paths.len()));
+    // One slot per file, so results keep the input order whichever thread finishes first
+    let files: Mutex<Vec<Option<FileResult<A>>>> = Mutex::new((0..file_paths.len()).map(|_| None).collect());
+    
+    std::thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, file_paths.len().max(1)) {
+            scope.spawn(|| loop {
+                // The run fails with that file's error anyway
+                if failed.load(Ordering::Relaxed) {
+                    break;
+                }
+                let index = next_file.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = file_paths.get(index) else { break };
+                let path = path.as_ref();
+                
+                let result = process_weather_file_silent_with::<A>(path, policy).map(|(file_stats, error_report)| {
+                    // Per-file tables are merged from `files` once every file is done
+                    let file_stats = if per_file {
+                        Some(file_stats)
+                    } else {
+                        merged.lock().unwrap().add(index, file_stats);
+                        None
+                    };
+                    FileStationStats { path: path.to_string(), station_stats: file_stats, error_report }
+                });
+                let result = result.map_err(|error| {
+                    failed.store(true, Ordering::Relaxed);
+                    WeatherError::InFile { path: path.to_string(), error: Box::new(error) }
+                });
+                files.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+    
+    // Files are only skipped after an earlier one failed, so its error is returned first
+    let files = files
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("Files after a failed one are the only ones skipped"))
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    let mut station_stats = merged.into_inner().unwrap().total;
+    for file_stats in files.iter().filter_map(|file| file.station_stats.as_ref()) {
+        merge_station_stats_ref(&mut station_stats, file_stats);
+    }
+    
+    Ok(MultiFileStationStats { files, station_stats })
+}
+
+type FileResult<A> = Result<FileStationStats<A>, WeatherError>;
+
+// Merges tables in input order: one that finishes ahead of an earlier file waits in `pending`
+// until everything before it has been merged
+struct OrderedMerge<A> {
+    total: HashTable<String, A>,
+    pending: Vec<Option<HashTable<String, A>>>,
+    next: usize,
+}
+
+impl<A: StationAccumulator> OrderedMerge<A> {
+    fn new(file_count: usize) -> Self {
+        Self {
+            total: HashTable::new(),
+            pending: (0..file_count).map(|_| None).collect(),
+            next: 0,
+        }
+    }
+
+    fn add(&mut self, index: usize, table: HashTable<String, A>) {
+        self.pending[index] = Some(table);
+        while let Some(table) = self.pending.get_mut(self.next).and_then(Option::take) {
+            merge_station_stats(&mut self.total, table);
+            self.next += 1;
+        }
+    }
+}
+
+// Prints the merged table and, with `options.per_file`, each file's table first, every section
+// headed by `==> path <==`. Returns the per-file results so the caller can report skipped lines.
+pub fn read_weather_files_with_policy<S: AsRef<str> + Sync>(
+    file_paths: &[S],
+    options: &OutputOptions,
+    policy: ErrorPolicy,
+    threads: usize,
+) -> Result<MultiFileStationStats, WeatherError> {
+    read_weather_files_with(file_paths, options, policy, threads)
+}
+
+// Same, keeping a histogram per station so `options.percentiles` can be reported
+pub fn read_weather_files_with_percentiles<S: AsRef<str> + Sync>(
+    file_paths: &[S],
+    options: &OutputOptions,
+    policy: ErrorPolicy,
+    threads: usize,
+) -> Result<MultiFileStationStats<StationHistogramStats>, WeatherError> {
+    read_weather_files_with(file_paths, options, policy, threads)
+}
+
+// Any accumulator; pick one with moments (StationVarianceStats) for `options.variance` and one
+// with a histogram (StationHistogramStats) for `options.percentiles`
+pub fn read_weather_files_with<A: StationAccumulator, S: AsRef<str> + Sync>(
+    file_paths: &[S],
+    options: &OutputOptions,
+    policy: ErrorPolicy,
+    threads: usize,
+) -> Result<MultiFileStationStats<A>, WeatherError> {
+    let results = process_weather_files_silent_with(file_paths, policy, threads, options.per_file)?;
+    
+    if options.per_file {
+        for file in &results.files {
+            println!("==> {} <==", file.path);
+            if let Some(station_stats) = &file.station_stats {
+                print_station_stats(station_stats, options);
+            }
+            println!();
+        }
+        println!("==> total <==");
+    }
+    
+    if results.station_stats.is_empty() {
+        eprintln!("No weather records found in the file.");
+    } else {
+        print_station_stats(&results.station_stats, options);
+    }
+    
+    Ok(results)
+}
+
+fn print_station_stats<A: StationAccumulator>(station_stats: &HashTable<String, A>, options: &OutputOptions) {
+    match options.format {
+        OutputFormat::Csv => {
+            // Output CSV headers
+            let mut header = String::from("Station,Records,MinTemperature,MaxTemperature,AvgTemperature");
+            if options.variance {
+                header.push_str(",Variance,StdDeviation");
+            }
+            for percentile in &options.percentiles {
+                header.push_str(&format!(",P{}", percentile));
             }
-            None => {
-                let new_stats = StationStats::new(record.station.clone(), record.temperature);
-                station_stats.insert(record.station, new_stats);
+            println!("{}", header);
+            
+            // Output each station's statistics in CSV format
+            for (station, accumulator) in station_stats.iter() {
+                let stats = accumulator.station_stats();
+                let mut row = format!("{},{},{:.1},{:.1},{:.1}",
+                                      station,
+                                      stats.count,
+                                      stats.min_temperature,
+                                      stats.max_temperature,
+                                      stats.avg_temperature());
+                if options.variance {
+                    let variance = accumulator.variance().unwrap_or(f64::NAN);
+                    row.push_str(&format!(",{:.1},{:.1}", variance, variance.sqrt()));
+                }
+                for &percentile in &options.percentiles {
+                    row.push_str(&format!(",{:.1}", accumulator.percentile(percentile).unwrap_or(f64::NAN)));
+                }
+                println!("{}", row);
             }
         }
+        OutputFormat::Challenge => {
+            println!("{}", format_challenge_output_with_options(station_stats, options));
+        }
     }
+}
+
+// Formats results as `{Abha=-23.0/18.0/59.2, ...}`, matching the reference
+// challenge output: stations sorted by name (UTF-16 code unit order, like
+// Java's String::compareTo) and values rounded half toward positive infinity.
+pub fn format_challenge_output(station_stats: &HashTable<String, StationStats>) -> String {
+    format_challenge_entries(station_stats, |stats| {
+        vec![stats.min_temperature, stats.avg_temperature(), stats.max_temperature]
+    })
+}
+
+// `{Abha=min/mean/max[/variance/stddev][/p1/p2/...], ...}` with the optional columns appended
+pub fn format_challenge_output_with_options<A: StationAccumulator>(station_stats: &HashTable<String, A>, options: &OutputOptions) -> String {
+    format_challenge_entries(station_stats, |accumulator| {
+        let stats = accumulator.station_stats();
+        let mut values = vec![stats.min_temperature, stats.avg_temperature(), stats.max_temperature];
+        if options.variance {
+            let variance = accumulator.variance().unwrap_or(f64::NAN);
+            values.extend([variance, variance.sqrt()]);
+        }
+        values.extend(options.percentiles.iter().map(|&percentile| accumulator.percentile(percentile).unwrap_or(f64::NAN)));
+        values
+    })
+}
+
+fn format_challenge_entries<A: Clone>(station_stats: &HashTable<String, A>, values: impl Fn(&A) -> Vec<f64>) -> String {
+    let mut entries: Vec<(&String, &A)> = station_stats.iter().collect();
+    entries.sort_unstable_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
     
-    Ok(station_stats)
+    let mut output = String::with_capacity(entries.len() * 32 + 2);
+    output.push('{');
+    
+    for (i, (station, stats)) in entries.iter().enumerate() {
+        if i > 0 {
+            output.push_str(", ");
+        }
+        output.push_str(station);
+        output.push('=');
+        for (j, value) in values(stats).into_iter().enumerate() {
+            if j > 0 {
+                output.push('/');
+            }
+            output.push_str(&format_challenge_temperature(value));
+        }
+    }
+    
+    output.push('}');
+    output
+}
+
+// Rounds to one decimal place the way the reference does (`Math.round(x * 10.0) / 10.0`):
+// half toward positive infinity, so 0.25 -> 0.3 and -0.25 -> -0.2. Negative zero prints as 0.0.
+pub fn format_challenge_temperature(value: f64) -> String {
+    // e.g. a percentile that couldn't be computed
+    if !value.is_finite() {
+        return value.to_string();
+    }
+    
+    let scaled = value * 10.0;
+    let floor = scaled.floor();
+    let rounded = if scaled - floor >= 0.5 { floor + 1.0 } else { floor };
+    let tenths = rounded as i64;
+    
+    let sign = if tenths < 0 { "-" } else { "" };
+    let abs = tenths.unsigned_abs();
+    format!("{}{}.{}", sign, abs / 10, abs % 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pipeline_current, PIPELINES};
+
+    const TEST_FILE: &str = "data/measurements_1KRecords.txt";
+    const GOLDEN_FILE: &str = "data/measurements_1KRecords.out";
+    const ROUNDING_FILE: &str = "data/measurements_rounding.txt";
+    const ROUNDING_GOLDEN_FILE: &str = "data/measurements_rounding.out";
+
+    #[test]
+    fn test_format_challenge_temperature_rounds_half_up() {
+        assert_eq!(format_challenge_temperature(0.25), "0.3");
+        assert_eq!(format_challenge_temperature(-0.25), "-0.2");
+        assert_eq!(format_challenge_temperature(0.05), "0.1");
+        assert_eq!(format_challenge_temperature(-0.05), "0.0");
+        assert_eq!(format_challenge_temperature(-0.0), "0.0");
+        assert_eq!(format_challenge_temperature(-23.0), "-23.0");
+        assert_eq!(format_challenge_temperature(59.2), "59.2");
+        assert_eq!(format_challenge_temperature(18.049), "18.0");
+        assert_eq!(format_challenge_temperature(-99.95), "-99.9");
+        assert_eq!(format_challenge_temperature(99.95), "100.0");
+        assert_eq!(format_challenge_temperature(f64::NAN), "NaN");
+    }
+
+    #[test]
+    fn test_format_challenge_output_sorted() {
+        let mut table: HashTable<String, StationStats> = HashTable::new();
+        for (station, temperature) in [("Zürich", 1.0), ("Abha", -23.0), ("Abha", 59.2), ("Abha", 18.0), ("Ürümqi", 7.45)] {
+            table
+                .entry(station.to_string())
+                .and_modify(|stats| stats.add_temperature(temperature))
+                .or_insert_with_key(|station| StationStats::new(station.clone(), temperature));
+        }
+        
+        assert_eq!(
+            format_challenge_output(&table),
+            "{Abha=-23.0/18.1/59.2, Zürich=1.0/1.0/1.0, Ürümqi=7.5/7.5/7.5}"
+        );
+        assert_eq!(format_challenge_output(&HashTable::new()), "{}");
+    }
+
+    #[test]
+    fn test_format_challenge_output_with_variance() {
+        let data = "Abha;10.0\nAbha;20.0\nAbha;30.0\n";
+        let options = OutputOptions { variance: true, percentiles: vec![50.0], ..OutputOptions::new(OutputFormat::Challenge) };
+        
+        let stats: HashTable<String, StationHistogramStats<StationVarianceStats>> =
+            crate::SliceRecordSource::new(data.as_bytes()).aggregate_with().unwrap();
+        assert_eq!(format_challenge_output_with_options(&stats, &options), "{Abha=10.0/20.0/30.0/66.7/8.2/20.0}");
+    }
+
+    #[test]
+    fn test_challenge_output_matches_golden_file() {
+        let expected = std::fs::read_to_string(GOLDEN_FILE).expect("Failed to read golden file");
+        
+        let stats = pipeline_current(TEST_FILE).expect("Failed to process file");
+        assert_eq!(format_challenge_output(&stats), expected.trim_end());
+        
+        let stats = process_weather_file_silent_mmap(TEST_FILE).expect("Failed to process file");
+        assert_eq!(format_challenge_output(&stats), expected.trim_end());
+    }
+
+    // Dozens of records per station, most of them averaging to exact ties such as 0.25 or -3.75.
+    // The expected means follow the Java reference's Math.round(x * 10.0) / 10.0, so half-even or
+    // half-away-from-zero rounding would fail here.
+    #[test]
+    fn test_challenge_output_rounds_means_like_reference() {
+        let expected = std::fs::read_to_string(ROUNDING_GOLDEN_FILE).expect("Failed to read golden file");
+        
+        for pipeline in PIPELINES {
+            let stats = (pipeline.run)(ROUNDING_FILE).expect("Failed to process file");
+            assert_eq!(format_challenge_output(&stats), expected.trim_end(), "{}", pipeline.name);
+        }
+    }
+
+    #[test]
+    fn test_process_weather_files_merges_tables() {
+        let single = process_weather_file_silent_mmap(TEST_FILE).expect("Failed to process file");
+        
+        for threads in [1, 4] {
+            let results = process_weather_files_silent(&[TEST_FILE, TEST_FILE, "data/sample_weather.csv"], ErrorPolicy::Strict, threads, true)
+                .expect("Failed to process files");
+            
+            assert_eq!(results.files.len(), 3);
+            assert_eq!(results.files[2].path, "data/sample_weather.csv");
+            assert_eq!(format_challenge_output(results.files[0].station_stats.as_ref().unwrap()), format_challenge_output(&single));
+            
+            let abram = single.get("Abram").unwrap();
+            let merged = results.station_stats.get("Abram").unwrap();
+            assert_eq!(merged.count, abram.count * 2);
+            assert_eq!(merged.min_temperature, abram.min_temperature);
+            assert_eq!(merged.max_temperature, abram.max_temperature);
+            assert_eq!(results.station_stats.get("Station1").unwrap().count, 2);
+        }
+    }
+
+    #[test]
+    fn test_process_weather_files_merges_in_input_order() {
+        // One station in every file, with sums whose f64 total depends on the merge order
+        let paths: Vec<String> = (0..8).map(|file| {
+            let path = std::env::temp_dir().join(format!("obr_merge_order_{}_{}.txt", std::process::id(), file));
+            let lines: String = (0..=file * 50).map(|i| format!("All;{:.1}\n", (i * 37 % 199) as f64 / 10.0 - 9.9)).collect();
+            std::fs::write(&path, lines).unwrap();
+            path.to_string_lossy().into_owned()
+        }).collect();
+        let sum = |paths: &[String], threads, per_file| {
+            let results = process_weather_files_silent(paths, ErrorPolicy::Strict, threads, per_file).unwrap();
+            results.station_stats.get("All").unwrap().sum_temperature
+        };
+        
+        let expected = sum(&paths, 1, false);
+        let reversed: Vec<String> = paths.iter().rev().cloned().collect();
+        assert_ne!(sum(&reversed, 1, false).to_bits(), expected.to_bits(), "the order has to matter for this test");
+        for _ in 0..10 {
+            for per_file in [false, true] {
+                assert_eq!(sum(&paths, 4, per_file).to_bits(), expected.to_bits());
+            }
+        }
+        
+        for path in &paths {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_process_weather_files_reports_failing_file() {
+        let error = match process_weather_files_silent(&[TEST_FILE, "data/bad_weather.csv"], ErrorPolicy::Strict, 2, false) {
+            Err(error) => error,
+            Ok(_) => panic!("Expected bad_weather.csv to fail"),
+        };
+        assert!(matches!(&error, WeatherError::InFile { path, .. } if path == "data/bad_weather.csv"));
+        assert_eq!(error.line(), Some(2));
+        
+        let results = process_weather_files_silent(&[TEST_FILE, "data/bad_weather.csv"], ErrorPolicy::lenient(), 2, false)
+            .expect("Lenient run should succeed");
+        assert!(results.files.iter().all(|file| file.station_stats.is_none()));
+        assert!(results.files[0].error_report.is_empty());
+        assert_eq!(results.files[1].error_report.len(), 1);
+        assert_eq!(results.skipped_lines(), 1);
+    }
+
+    #[test]
+    fn test_process_weather_files_rejects_repeated_stdin() {
+        let error = match process_weather_files_silent(&[STDIN_PATH, TEST_FILE, STDIN_PATH], ErrorPolicy::Strict, 1, false) {
+            Err(error) => error,
+            Ok(_) => panic!("Expected a second '-' to be rejected"),
+        };
+        assert!(matches!(&error, WeatherError::Io(error) if error.kind() == io::ErrorKind::InvalidInput));
+    }
+
+    #[test]
+    fn test_read_weather_file_with_pipeline() {
+        let options = OutputOptions::new(OutputFormat::Challenge);
+        for pipeline in crate::PIPELINES {
+            read_weather_file_with_pipeline(TEST_FILE, pipeline, &options).unwrap();
+        }
+        
+        let streaming = crate::find_pipeline("streaming").unwrap();
+        let error = read_weather_file_with_pipeline("data/bad_weather.csv", streaming, &options).unwrap_err();
+        assert_eq!(error.kind(), Some(crate::ErrorKind::BadNumber));
+    }
 }
@@ -0,0 +1,145 @@
+// Grow once more than 3/4 of the slots would be occupied
+const LOAD_FACTOR_NUMERATOR: usize = 3;
+const LOAD_FACTOR_DENOMINATOR: usize = 4;
+
+pub(crate) struct Bucket<K, V> {
+    pub(crate) hash: u64,
+    pub(crate) key: K,
+    pub(crate) value: V,
+}
+
+// Open-addressing storage with linear probing over a power-of-two number of slots, shared by
+// HashTable and ByteHashTable. Every entry keeps its full hash, so growth never re-hashes a key
+// and most mismatching probes are rejected without comparing keys. Removal uses backward-shift
+// deletion, so no tombstones are ever left behind. Hashing and key comparison are left to the
+// tables on top.
+pub(crate) struct RawTable<K, V> {
+    slots: Vec<Option<Bucket<K, V>>>,
+    size: usize,
+    mask: usize,
+}
+
+impl<K, V> RawTable<K, V> {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1).next_power_of_two();
+
+        Self {
+            slots: Self::empty_slots(capacity),
+            size: 0,
+            mask: capacity - 1,
+        }
+    }
+
+    fn empty_slots(capacity: usize) -> Vec<Option<Bucket<K, V>>> {
+        let mut slots = Vec::with_capacity(capacity);
+        slots.resize_with(capacity, || None);
+        slots
+    }
+
+    #[inline]
+    pub(crate) fn capacity(&self) -> usize {
+        self.mask + 1
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.size
+    }
+
+    #[inline]
+    fn ideal_index(&self, hash: u64) -> usize {
+        (hash as usize) & self.mask
+    }
+
+    // Grows if needed so one more entry can be added without resizing
+    pub(crate) fn reserve_one(&mut self) {
+        if (self.size + 1) * LOAD_FACTOR_DENOMINATOR <= self.capacity() * LOAD_FACTOR_NUMERATOR {
+            return;
+        }
+
+        let new_capacity = self.capacity() * 2;
+        let old_slots = std::mem::replace(&mut self.slots, Self::empty_slots(new_capacity));
+        self.mask = new_capacity - 1;
+
+        // Reuse the stored hashes; keys are never re-hashed on growth
+        for bucket in old_slots.into_iter().flatten() {
+            let index = self.probe_vacant(bucket.hash);
+            self.slots[index] = Some(bucket);
+        }
+    }
+
+    fn probe_vacant(&self, hash: u64) -> usize {
+        let mut index = self.ideal_index(hash);
+        while self.slots[index].is_some() {
+            index = (index + 1) & self.mask;
+        }
+        index
+    }
+
+    #[inline]
+    pub(crate) fn find(&self, hash: u64, matches: impl Fn(&K) -> bool) -> Option<usize> {
+        let mut index = self.ideal_index(hash);
+
+        // The load factor guarantees at least one empty slot, so the probe terminates
+        loop {
+            match &self.slots[index] {
+                None => return None,
+                Some(bucket) if bucket.hash == hash && matches(&bucket.key) => return Some(index),
+                Some(_) => index = (index + 1) & self.mask,
+            }
+        }
+    }
+
+    // Adds a key that `find` just missed; `reserve_one` must have been called first
+    pub(crate) fn insert_new(&mut self, hash: u64, key: K, value: V) -> usize {
+        debug_assert!((self.size + 1) * LOAD_FACTOR_DENOMINATOR <= self.capacity() * LOAD_FACTOR_NUMERATOR);
+        let index = self.probe_vacant(hash);
+        self.slots[index] = Some(Bucket { hash, key, value });
+        self.size += 1;
+        index
+    }
+
+    #[inline]
+    pub(crate) fn bucket(&self, index: usize) -> &Bucket<K, V> {
+        self.slots[index].as_ref().expect("index points at an occupied slot")
+    }
+
+    #[inline]
+    pub(crate) fn bucket_mut(&mut self, index: usize) -> &mut Bucket<K, V> {
+        self.slots[index].as_mut().expect("index points at an occupied slot")
+    }
+
+    pub(crate) fn remove(&mut self, index: usize) -> Bucket<K, V> {
+        let mut hole = index;
+        let removed = self.slots[hole].take().expect("index points at an occupied slot");
+        self.size -= 1;
+
+        // Backward-shift the rest of the probe run into the hole
+        let mut index = (hole + 1) & self.mask;
+        while let Some(bucket) = &self.slots[index] {
+            let distance_from_ideal = index.wrapping_sub(self.ideal_index(bucket.hash)) & self.mask;
+            let distance_from_hole = index.wrapping_sub(hole) & self.mask;
+
+            if distance_from_ideal >= distance_from_hole {
+                self.slots[hole] = self.slots[index].take();
+                hole = index;
+            }
+            index = (index + 1) & self.mask;
+        }
+
+        removed
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Bucket<K, V>> {
+        self.slots.iter().flatten()
+    }
+}
+
+impl<K, V> IntoIterator for RawTable<K, V> {
+    type Item = Bucket<K, V>;
+    type IntoIter = std::iter::Flatten<std::vec::IntoIter<Option<Bucket<K, V>>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.slots.into_iter().flatten()
+    }
+}
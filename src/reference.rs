@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+use crate::{neumaier_add, ErrorKind, HashTable, ParseError, StationStats, WeatherError};
+
+// Saved outputs print temperatures with one decimal, so comparisons against them allow the
+// rounding error on top of the requested tolerance
+const ROUNDING_SLACK: f64 = 0.05 + 1e-9;
+
+// The figures `validate` compares for one station; `count` is unknown for challenge-format
+// output, which doesn't print it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferenceStats {
+    pub count: Option<usize>,
+    pub min: f64,
+    pub mean: f64,
+    pub max: f64,
+}
+
+// Per-station answer for a whole input, from a pipeline, the oracle or a saved output file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Reference {
+    pub stations: BTreeMap<String, ReferenceStats>,
+    // Values were rounded to one decimal when saved
+    pub rounded: bool,
+}
+
+impl Reference {
+    pub fn from_station_stats(station_stats: &HashTable<String, StationStats>) -> Self {
+        let stations = station_stats
+            .iter()
+            .map(|(station, stats)| {
+                let reference = ReferenceStats {
+                    count: Some(stats.count),
+                    min: stats.min_temperature,
+                    mean: stats.avg_temperature(),
+                    max: stats.max_temperature,
+                };
+                (station.clone(), reference)
+            })
+            .collect();
+        Self { stations, rounded: false }
+    }
+
+    // Parses output saved from obr: the challenge format (`{Abha=-23.0/18.0/59.2, ...}`) or
+    // the CSV format (`Station,Records,MinTemperature,MaxTemperature,AvgTemperature[,...]`).
+    // Extra columns such as variance or percentiles are ignored.
+    pub fn parse_output(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let stations = if text.starts_with('{') {
+            parse_challenge_output(text)?
+        } else if text.starts_with("Station,") {
+            parse_csv_output(text)?
+        } else {
+            return Err("Expected challenge output ('{...}') or CSV output with a 'Station,' header".to_string());
+        };
+        Ok(Self { stations, rounded: true })
+    }
+}
+
+fn parse_challenge_output(text: &str) -> Result<BTreeMap<String, ReferenceStats>, String> {
+    let body = text.strip_prefix('{').and_then(|body| body.strip_suffix('}')).ok_or("Challenge output must be wrapped in '{...}'")?;
+    let mut stations = BTreeMap::new();
+    if body.trim().is_empty() {
+        return Ok(stations);
+    }
+
+    // Station names may contain ", " themselves, so a piece only ends an entry once it has
+    // a `=min/mean/max` tail
+    let mut pending = String::new();
+    for piece in body.split(", ") {
+        if !pending.is_empty() {
+            pending.push_str(", ");
+        }
+        pending.push_str(piece);
+
+        let Some((station, values)) = pending.rsplit_once('=') else { continue };
+        let values: Option<Vec<f64>> = values.split('/').map(|value| value.parse().ok()).collect();
+        match values.as_deref() {
+            Some([min, mean, max, ..]) => {
+                stations.insert(station.to_string(), ReferenceStats { count: None, min: *min, mean: *mean, max: *max });
+                pending.clear();
+            }
+            _ => continue,
+        }
+    }
+
+    if !pending.is_empty() {
+        return Err(format!("Cannot parse challenge entry '{}'", pending));
+    }
+    Ok(stations)
+}
+
+fn parse_csv_output(text: &str) -> Result<BTreeMap<String, ReferenceStats>, String> {
+    let mut lines = text.lines();
+    let header: Vec<&str> = lines.next().unwrap_or_default().split(',').collect();
+    if header.get(1..5) != Some(&["Records", "MinTemperature", "MaxTemperature", "AvgTemperature"][..]) {
+        return Err(format!("Unexpected CSV header '{}'", header.join(",")));
+    }
+
+    let mut stations = BTreeMap::new();
+    for (i, line) in lines.enumerate() {
+        // Station names aren't quoted, so split the value columns off from the right
+        let mut fields: Vec<&str> = line.rsplitn(header.len(), ',').collect();
+        fields.reverse();
+        let error = || format!("Line {}: cannot parse CSV row '{}'", i + 2, line);
+        let [station, count, min, max, mean, ..] = fields[..] else {
+            return Err(error());
+        };
+        let number = |value: &str| value.parse::<f64>().map_err(|_| error());
+        let reference = ReferenceStats {
+            count: Some(count.parse().map_err(|_| error())?),
+            min: number(min)?,
+            mean: number(mean)?,
+            max: number(max)?,
+        };
+        stations.insert(station.to_string(), reference);
+    }
+    Ok(stations)
+}
+
+// Slow, independent implementation of the input rules for checking the optimized pipelines:
+// whole-line string handling, no shared parser and no custom hash table. Errors only locate
+// the line (column 1, whole line as bytes).
+pub fn oracle_reference(data: &[u8]) -> Result<Reference, WeatherError> {
+    const BOM: &[u8] = b"\xEF\xBB\xBF";
+    let body = data.strip_prefix(BOM).unwrap_or(data);
+    let mut offset = data.len() - body.len();
+
+    // (count, min, max, sum, compensation)
+    let mut totals: BTreeMap<String, (usize, f64, f64, f64, f64)> = BTreeMap::new();
+
+    for (index, line) in body.split(|&byte| byte == b'\n').enumerate() {
+        let line_offset = offset;
+        offset += line.len() + 1;
+        let error = |kind| WeatherError::Parse(ParseError::new(kind, index + 1, 1, line_offset, line));
+
+        let text = line.trim_ascii();
+        if text.is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&[u8]> = text.split(|&byte| byte == b';').collect();
+        let (station, temperature) = match fields[..] {
+            [_] => return Err(error(ErrorKind::MissingDelimiter)),
+            [station, temperature] => (station.trim_ascii(), temperature.trim_ascii()),
+            _ => return Err(error(ErrorKind::TooManyColumns)),
+        };
+        if station.is_empty() {
+            return Err(error(ErrorKind::EmptyStation));
+        }
+        let station = std::str::from_utf8(station).map_err(|_| error(ErrorKind::InvalidUtf8))?;
+        let temperature: f64 = std::str::from_utf8(temperature)
+            .map_err(|_| error(ErrorKind::InvalidUtf8))?
+            .parse()
+            .ok()
+            .filter(|value: &f64| value.is_finite())
+            .ok_or_else(|| error(ErrorKind::BadNumber))?;
+
+        let entry = totals.entry(station.to_string()).or_insert((0, f64::INFINITY, f64::NEG_INFINITY, 0.0, 0.0));
+        entry.0 += 1;
+        entry.1 = entry.1.min(temperature);
+        entry.2 = entry.2.max(temperature);
+        neumaier_add(&mut entry.3, &mut entry.4, temperature);
+    }
+
+    let stations = totals
+        .into_iter()
+        .map(|(station, (count, min, max, sum, compensation))| {
+            (station, ReferenceStats { count: Some(count), min, mean: (sum + compensation) / count as f64, max })
+        })
+        .collect();
+    Ok(Reference { stations, rounded: false })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StationDiff {
+    // In the reference but not in the result
+    Missing { station: String },
+    // In the result but not in the reference
+    Unexpected { station: String },
+    Mismatch { station: String, field: &'static str, expected: f64, actual: f64 },
+}
+
+impl std::fmt::Display for StationDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StationDiff::Missing { station } => write!(f, "{}: missing from result", station),
+            StationDiff::Unexpected { station } => write!(f, "{}: not in reference", station),
+            StationDiff::Mismatch { station, field, expected, actual } => {
+                write!(f, "{}: {} expected {} got {} (diff {:+})", station, field, expected, actual, actual - expected)
+            }
+        }
+    }
+}
+
+// Lists every station whose count differs or whose min, max or mean differs by more than
+// `tolerance` (plus the rounding slack when `expected` was read from a saved output)
+pub fn compare_references(expected: &Reference, actual: &Reference, tolerance: f64) -> Vec<StationDiff> {
+    let tolerance = if expected.rounded { tolerance + ROUNDING_SLACK } else { tolerance };
+    let mut diffs = Vec::new();
+
+    for (station, expected_stats) in &expected.stations {
+        let Some(actual_stats) = actual.stations.get(station) else {
+            diffs.push(StationDiff::Missing { station: station.clone() });
+            continue;
+        };
+
+        let mismatch = |field, expected: f64, actual: f64| StationDiff::Mismatch { station: station.clone(), field, expected, actual };
+        if let (Some(expected_count), Some(actual_count)) = (expected_stats.count, actual_stats.count)
+            && expected_count != actual_count
+        {
+            diffs.push(mismatch("count", expected_count as f64, actual_count as f64));
+        }
+        for (field, expected_value, actual_value) in [
+            ("min", expected_stats.min, actual_stats.min),
+            ("max", expected_stats.max, actual_stats.max),
+            ("mean", expected_stats.mean, actual_stats.mean),
+        ] {
+            // NaN on either side counts as a difference
+            let within = (actual_value - expected_value).abs() <= tolerance;
+            if !within {
+                diffs.push(mismatch(field, expected_value, actual_value));
+            }
+        }
+    }
+
+    for station in actual.stations.keys().filter(|station| !expected.stations.contains_key(*station)) {
+        diffs.push(StationDiff::Unexpected { station: station.clone() });
+    }
+
+    diffs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pipeline_current, LineParser, PIPELINES};
+
+    const TEST_FILE: &str = "data/measurements_1KRecords.txt";
+    const GOLDEN_FILE: &str = "data/measurements_1KRecords.out";
+
+    #[test]
+    fn test_every_pipeline_matches_oracle() {
+        let oracle = oracle_reference(&std::fs::read(TEST_FILE).unwrap()).unwrap();
+        assert_eq!(oracle.stations.values().filter_map(|stats| stats.count).sum::<usize>(), 1000);
+
+        for pipeline in PIPELINES {
+            let actual = Reference::from_station_stats(&(pipeline.run)(TEST_FILE).unwrap());
+            assert_eq!(compare_references(&oracle, &actual, 1e-9), vec![], "{}", pipeline.name);
+        }
+    }
+
+    #[test]
+    fn test_saved_output_matches_pipeline() {
+        let saved = Reference::parse_output(&std::fs::read_to_string(GOLDEN_FILE).unwrap()).unwrap();
+        let actual = Reference::from_station_stats(&pipeline_current(TEST_FILE).unwrap());
+        assert!(saved.rounded);
+        assert_eq!(saved.stations.len(), actual.stations.len());
+        assert_eq!(compare_references(&saved, &actual, 0.0), vec![]);
+    }
+
+    #[test]
+    fn test_compare_reports_each_difference() {
+        let stats = |count, min, mean, max| ReferenceStats { count, min, mean, max };
+        let mut expected = Reference::default();
+        expected.stations.insert("Hamburg".to_string(), stats(Some(2), -3.4, 4.3, 12.0));
+        expected.stations.insert("Bulawayo".to_string(), stats(Some(1), 8.9, 8.9, 8.9));
+
+        let mut actual = Reference::default();
+        actual.stations.insert("Hamburg".to_string(), stats(Some(3), -3.4, 4.30001, 12.5));
+        actual.stations.insert("Palembang".to_string(), stats(Some(1), 38.8, 38.8, 38.8));
+
+        let diffs = compare_references(&expected, &actual, 0.001);
+        assert_eq!(diffs, vec![
+            StationDiff::Missing { station: "Bulawayo".to_string() },
+            StationDiff::Mismatch { station: "Hamburg".to_string(), field: "count", expected: 2.0, actual: 3.0 },
+            StationDiff::Mismatch { station: "Hamburg".to_string(), field: "max", expected: 12.0, actual: 12.5 },
+            StationDiff::Unexpected { station: "Palembang".to_string() },
+        ]);
+        assert_eq!(compare_references(&expected, &actual, 0.0).len(), 5);
+    }
+
+    #[test]
+    fn test_parse_output_formats() {
+        let challenge = Reference::parse_output("{Abha=-23.0/18.0/59.2, Washington, D.C.=-15.0/14.6/52.0/1.0/2.0}\n").unwrap();
+        assert_eq!(challenge.stations.len(), 2);
+        assert_eq!(challenge.stations["Washington, D.C."], ReferenceStats { count: None, min: -15.0, mean: 14.6, max: 52.0 });
+
+        let csv = Reference::parse_output("Station,Records,MinTemperature,MaxTemperature,AvgTemperature,Variance,StdDeviation\nNew York, NY,3,-1.5,20.0,8.2,1.0,1.0\n").unwrap();
+        assert_eq!(csv.stations["New York, NY"], ReferenceStats { count: Some(3), min: -1.5, mean: 8.2, max: 20.0 });
+
+        assert_eq!(Reference::parse_output("{}").unwrap().stations.len(), 0);
+        assert!(Reference::parse_output("{Abha=-23.0/18.0}").is_err());
+        assert!(Reference::parse_output("Hamburg;12.0").is_err());
+    }
+
+    #[test]
+    fn test_oracle_rejects_like_line_parser() {
+        let parser = LineParser::new();
+        for input in [&b"Bulawayo 8.9"[..], b"Hamburg;1;2", b" ;1.0", b"Hamburg;abc", b"Hamburg;NaN", b"Ham\xffburg;1.0", b"Hamburg;1\xff"] {
+            let expected = parser.parse(1, 0, input).unwrap_err();
+            let actual = oracle_reference(input).unwrap_err();
+            assert_eq!((actual.kind(), actual.line()), (Some(expected.kind), Some(1)), "{:?}", String::from_utf8_lossy(input));
+        }
+
+        let oracle = oracle_reference(b"\xEF\xBB\xBF Hamburg ; 12.0 \r\n\n  \nHamburg;-3.4").unwrap();
+        assert_eq!(oracle.stations["Hamburg"], ReferenceStats { count: Some(2), min: -3.4, mean: 4.3, max: 12.0 });
+    }
+}
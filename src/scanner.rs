@@ -0,0 +1,272 @@
+use crate::{BytesHasher, ErrorPolicy, ErrorReport, LineParser, ParsedRecord, ParseError, RecordSource, WeatherError};
+
+const ONES: u64 = 0x0101_0101_0101_0101;
+const HIGHS: u64 = 0x8080_8080_8080_8080;
+const SEMICOLONS: u64 = ONES * b';' as u64;
+const NEWLINES: u64 = ONES * b'\n' as u64;
+const HASH_MULTIPLIER: u64 = 0x517C_C1B7_2722_0A95;
+
+// Sets the high bit of every byte of `word` equal to the byte in `pattern`.
+// Only the lowest set bit is exact, which is all the scanner relies on.
+#[inline(always)]
+fn match_bytes(word: u64, pattern: u64) -> u64 {
+    let x = word ^ pattern;
+    x.wrapping_sub(ONES) & !x & HIGHS
+}
+
+#[inline(always)]
+fn mix_word(hash: u64, word: u64) -> u64 {
+    (hash.rotate_left(5) ^ word).wrapping_mul(HASH_MULTIPLIER)
+}
+
+#[inline(always)]
+fn finalize(hash: u64, len: usize) -> u64 {
+    let mut hash = hash ^ len as u64;
+    hash ^= hash >> 37;
+    hash = hash.wrapping_mul(0x1656_6791_9E37_79F9);
+    hash ^= hash >> 32;
+    hash
+}
+
+#[inline(always)]
+fn load_word(data: &[u8], position: usize) -> u64 {
+    match data.get(position..position + 8) {
+        Some(bytes) => u64::from_le_bytes(bytes.try_into().unwrap()),
+        None => {
+            // Zero padding never matches ';' or '\n'
+            let mut padded = [0u8; 8];
+            let tail = &data[position..];
+            padded[..tail.len()].copy_from_slice(tail);
+            u64::from_le_bytes(padded)
+        }
+    }
+}
+
+// Word-at-a-time hash over little-endian 8-byte chunks, matching what the fused scanner
+// computes while it searches for the delimiter
+pub fn fused_station_hash(station: &[u8]) -> u64 {
+    let mut hash = 0;
+    let mut chunks = station.chunks_exact(8);
+    for chunk in &mut chunks {
+        hash = mix_word(hash, u64::from_le_bytes(chunk.try_into().unwrap()));
+    }
+    let remainder = chunks.remainder();
+    if !remainder.is_empty() {
+        let mut padded = [0u8; 8];
+        padded[..remainder.len()].copy_from_slice(remainder);
+        hash = mix_word(hash, u64::from_le_bytes(padded));
+    }
+    finalize(hash, station.len())
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FusedHashBuilder;
+
+impl BytesHasher for FusedHashBuilder {
+    #[inline]
+    fn hash_bytes(&self, bytes: &[u8]) -> u64 {
+        fused_station_hash(bytes)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FusedRecord<'a> {
+    pub station: &'a str,
+    pub station_hash: u64,
+    pub temperature: f64,
+}
+
+// Single pass over the input: each word is checked for ';' and '\n' and folded into the
+// station hash as it goes, so the station bytes are only read once. Field rules are
+// delegated to LineParser once the delimiter is found.
+pub struct FusedScanner<'a> {
+    data: &'a [u8],
+    position: usize,
+    line_number: usize,
+    parser: LineParser,
+    policy: ErrorPolicy,
+    report: ErrorReport,
+}
+
+impl<'a> FusedScanner<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+            line_number: 0,
+            parser: LineParser::new(),
+            policy: ErrorPolicy::Strict,
+            report: ErrorReport::new(),
+        }
+    }
+
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn into_error_report(self) -> ErrorReport {
+        self.report
+    }
+
+    fn skip_line(&mut self, from: usize) {
+        self.position = match memchr::memchr(b'\n', &self.data[from..]) {
+            Some(offset) => from + offset + 1,
+            None => self.data.len(),
+        };
+    }
+
+    // Returns Ok(None) for blank lines
+    fn scan_line(&mut self) -> Result<Option<FusedRecord<'a>>, ParseError> {
+        let data = self.data;
+        let start = self.position;
+        let mut position = start;
+        let mut hash = 0;
+
+        let semicolon = loop {
+            if position >= data.len() {
+                // Trailing bytes without a delimiter
+                self.position = data.len();
+                self.parser.find_delimiter(self.line_number, start, &data[start..])?;
+                return Ok(None);
+            }
+
+            let word = load_word(data, position);
+            let matches = match_bytes(word, SEMICOLONS) | match_bytes(word, NEWLINES);
+            if matches == 0 {
+                hash = mix_word(hash, word);
+                position += 8;
+                continue;
+            }
+
+            let offset = (matches.trailing_zeros() / 8) as usize;
+            let found = position + offset;
+            if data[found] == b'\n' {
+                self.position = found + 1;
+                self.parser.find_delimiter(self.line_number, start, &data[start..found])?;
+                return Ok(None);
+            }
+
+            if offset > 0 {
+                hash = mix_word(hash, word & (u64::MAX >> (64 - 8 * offset)));
+            }
+            break found;
+        };
+
+        self.skip_line(semicolon + 1);
+        let line_end = if data[self.position - 1] == b'\n' { self.position - 1 } else { self.position };
+        let raw_station_len = semicolon - start;
+        let record = self.parser.parse_fields(self.line_number, start, &data[start..line_end], raw_station_len)?;
+
+        // Padded names are rare; re-hash the trimmed name so keys match the other parsers
+        let station_hash = if record.station.len() == raw_station_len {
+            finalize(hash, raw_station_len)
+        } else {
+            fused_station_hash(record.station.as_bytes())
+        };
+
+        Ok(Some(FusedRecord { station: record.station, station_hash, temperature: record.temperature }))
+    }
+}
+
+impl<'a> Iterator for FusedScanner<'a> {
+    type Item = Result<FusedRecord<'a>, WeatherError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position < self.data.len() {
+            self.line_number += 1;
+            let line_offset = self.position;
+            match self.scan_line() {
+                Ok(None) => continue,
+                Ok(Some(record)) => return Some(Ok(record)),
+                Err(error) => {
+                    // scan_line has already moved past the rejected line
+                    let line_end = if self.data[self.position - 1] == b'\n' { self.position - 1 } else { self.position };
+                    let line = &self.data[line_offset..line_end];
+                    if let Err(e) = self.report.reject(self.policy, line_offset, line, error) {
+                        return Some(Err(e));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<'a> RecordSource for FusedScanner<'a> {
+    fn next_record(&mut self) -> Option<Result<ParsedRecord<'_>, WeatherError>> {
+        self.next().map(|record| record.map(|record| ParsedRecord { station: record.station, temperature: record.temperature }))
+    }
+
+    fn error_report(&self) -> &ErrorReport {
+        &self.report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    fn scan(data: &[u8]) -> Result<Vec<FusedRecord<'_>>, WeatherError> {
+        FusedScanner::new(data).collect()
+    }
+
+    #[test]
+    fn test_scanner_hash_matches_standalone_hash() {
+        let data = b"A;1.0\nHamburg;12.0\nExactly8;-3.5\nA much longer station name;99.9\nNo newline at end;0.0";
+        let records = scan(data).unwrap();
+
+        let stations: Vec<&str> = records.iter().map(|r| r.station).collect();
+        assert_eq!(stations, vec![
+            "A", "Hamburg", "Exactly8", "A much longer station name", "No newline at end",
+        ]);
+        for record in &records {
+            assert_eq!(record.station_hash, fused_station_hash(record.station.as_bytes()));
+        }
+        assert_eq!(records[2].temperature, -3.5);
+        assert_eq!(records[3].temperature, 99.9);
+    }
+
+    #[test]
+    fn test_scanner_trims_and_skips_blank_lines() {
+        let records = scan(b"\n  Station1  ; 25.5 \r\n   \n\nStation2;-10.2\n\n").unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].station, "Station1");
+        assert_eq!(records[0].station_hash, fused_station_hash(b"Station1"));
+        assert_eq!(records[0].temperature, 25.5);
+        assert_eq!(records[1].temperature, -10.2);
+    }
+
+    #[test]
+    fn test_scanner_errors() {
+        let error = scan(b"Station1;25.5\nStation2 25.5\n").unwrap_err();
+        assert_eq!(error.kind(), Some(ErrorKind::MissingDelimiter));
+        assert_eq!(error.line(), Some(2));
+        
+        match scan(b"Station1;25.5\n ;1.0").unwrap_err() {
+            WeatherError::Parse(error) => {
+                assert_eq!(error.kind, ErrorKind::EmptyStation);
+                assert_eq!((error.line, error.column, error.byte_offset), (2, 1, 14));
+                assert_eq!(error.bytes, b" ");
+            }
+            other => panic!("Expected Parse error, got {:?}", other),
+        }
+        match scan(b"Station1;invalid_temp").unwrap_err() {
+            WeatherError::Parse(error) => {
+                assert_eq!(error.kind, ErrorKind::BadNumber);
+                assert_eq!((error.line, error.column, error.byte_offset), (1, 10, 9));
+                assert_eq!(error.bytes, b"invalid_temp");
+            }
+            other => panic!("Expected Parse error, got {:?}", other),
+        }
+        assert_eq!(scan(b"Station1;25.5\nNoDelimiterAtEof").unwrap_err().kind(), Some(ErrorKind::MissingDelimiter));
+    }
+
+    #[test]
+    fn test_scanner_accepts_general_numbers() {
+        let records = scan(b"Station1;25\nStation2;1.25\n").unwrap();
+        assert_eq!(records[0].temperature, 25.0);
+        assert_eq!(records[1].temperature, 1.25);
+    }
+}
@@ -1,8 +1,9 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
-use std::str::FromStr;
 use memmap2::MmapOptions;
+use crate::HashTable;
+use crate::parser::{accept_line, next_line, LineParser, ParsedRecord, RecordSource};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct WeatherRecord {
@@ -16,11 +17,57 @@ impl WeatherRecord {
     }
 }
 
+// Borrowed record pointing straight into the input buffer (e.g. an mmap)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeatherRecordRef<'a> {
+    pub station: &'a [u8],
+    pub temperature: f64,
+}
+
+impl<'a> WeatherRecordRef<'a> {
+    pub fn new(station: &'a [u8], temperature: f64) -> Self {
+        Self { station, temperature }
+    }
+}
+
+// Temperature stored as fixed-point tenths of a degree (e.g. -12.3 -> -123)
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeatherRecordFixed {
+    pub station: String,
+    pub temperature: i16,
+}
+
+impl WeatherRecordFixed {
+    pub fn new(station: String, temperature: i16) -> Self {
+        Self { station, temperature }
+    }
+}
+
 #[derive(Debug)]
 pub enum WeatherError {
     Io(std::io::Error),
-    Parse(String),
-    InvalidFormat(String),
+    Parse(ParseError),
+    TooManyErrors(usize),
+    // An error from one of several inputs, tagged with the path it came from
+    InFile { path: String, error: Box<WeatherError> },
+}
+
+impl WeatherError {
+    pub fn kind(&self) -> Option<ErrorKind> {
+        match self {
+            WeatherError::Parse(error) => Some(error.kind),
+            WeatherError::InFile { error, .. } => error.kind(),
+            _ => None,
+        }
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            WeatherError::Parse(error) => Some(error.line),
+            WeatherError::InFile { error, .. } => error.line(),
+            _ => None,
+        }
+    }
 }
 
 impl From<std::io::Error> for WeatherError {
@@ -29,20 +76,192 @@ impl From<std::io::Error> for WeatherError {
     }
 }
 
+impl From<ParseError> for WeatherError {
+    fn from(error: ParseError) -> Self {
+        WeatherError::Parse(error)
+    }
+}
+
 impl std::fmt::Display for WeatherError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             WeatherError::Io(err) => write!(f, "I/O error: {}", err),
-            WeatherError::Parse(msg) => write!(f, "Parse error: {}", msg),
-            WeatherError::InvalidFormat(msg) => write!(f, "Invalid format: {}", msg),
+            WeatherError::Parse(err) => write!(f, "Parse error: {}", err),
+            WeatherError::TooManyErrors(limit) => write!(f, "Too many malformed lines (limit is {})", limit),
+            WeatherError::InFile { path, error } => write!(f, "{}: {}", path, error),
         }
     }
 }
 
 impl std::error::Error for WeatherError {}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    MissingDelimiter,
+    TooManyColumns,
+    EmptyStation,
+    BadNumber,
+    InvalidUtf8,
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ErrorKind::MissingDelimiter => "missing delimiter",
+            ErrorKind::TooManyColumns => "too many columns",
+            ErrorKind::EmptyStation => "empty station",
+            ErrorKind::BadNumber => "bad number",
+            ErrorKind::InvalidUtf8 => "invalid UTF-8",
+        };
+        f.write_str(name)
+    }
+}
+
+// A rejected line: `line` and `column` are 1-based, `byte_offset` is the position of the
+// offending bytes in the whole input and `bytes` holds those bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ErrorKind,
+    pub line: usize,
+    pub column: usize,
+    pub byte_offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+impl ParseError {
+    pub fn new(kind: ErrorKind, line: usize, column: usize, byte_offset: usize, bytes: &[u8]) -> Self {
+        Self { kind, line, column, byte_offset, bytes: bytes.to_vec() }
+    }
+
+    // Locates `part`, which must be a subslice of `line_bytes`, in a line starting at `line_offset`
+    pub(crate) fn in_line(kind: ErrorKind, line: usize, line_offset: usize, line_bytes: &[u8], part: &[u8]) -> Self {
+        let index = part.as_ptr() as usize - line_bytes.as_ptr() as usize;
+        debug_assert!(index + part.len() <= line_bytes.len());
+        Self::new(kind, line, index + 1, line_offset + index, part)
+    }
+
+    // Points at the first invalid sequence inside `part`
+    pub(crate) fn invalid_utf8(line: usize, line_offset: usize, line_bytes: &[u8], part: &[u8], error: std::str::Utf8Error) -> Self {
+        let start = error.valid_up_to();
+        let end = error.error_len().map_or(part.len(), |len| start + len);
+        Self::in_line(ErrorKind::InvalidUtf8, line, line_offset, line_bytes, &part[start..end])
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Line {}, column {}: ", self.line, self.column)?;
+        let bytes = String::from_utf8_lossy(&self.bytes);
+        match self.kind {
+            ErrorKind::MissingDelimiter => write!(f, "no ';' delimiter found in '{}'", bytes),
+            ErrorKind::TooManyColumns => write!(f, "unexpected extra column '{}'", bytes),
+            ErrorKind::EmptyStation => write!(f, "weather station name cannot be empty"),
+            ErrorKind::BadNumber => write!(f, "cannot parse temperature '{}' as a number", bytes),
+            ErrorKind::InvalidUtf8 => write!(f, "invalid UTF-8 sequence {:02x?}", self.bytes),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Strict aborts on the first malformed line. Lenient skips malformed lines and records
+// them in an ErrorReport; with `max_errors` set, exceeding the cap aborts with TooManyErrors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    #[default]
+    Strict,
+    Lenient { max_errors: Option<usize> },
+}
+
+impl ErrorPolicy {
+    pub fn lenient() -> Self {
+        ErrorPolicy::Lenient { max_errors: None }
+    }
+
+    pub fn lenient_with_limit(max_errors: usize) -> Self {
+        ErrorPolicy::Lenient { max_errors: Some(max_errors) }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedLine {
+    pub line_number: usize,
+    pub byte_offset: usize,
+    pub content: Vec<u8>,
+    pub kind: ErrorKind,
+    pub error: ParseError,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ErrorReport {
+    skipped: Vec<SkippedLine>,
+}
+
+impl ErrorReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn skipped(&self) -> &[SkippedLine] {
+        &self.skipped
+    }
+
+    pub fn len(&self) -> usize {
+        self.skipped.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.skipped.is_empty()
+    }
+
+    pub fn count_of(&self, kind: ErrorKind) -> usize {
+        self.skipped.iter().filter(|line| line.kind == kind).count()
+    }
+
+    pub fn merge(&mut self, other: ErrorReport) {
+        self.skipped.extend(other.skipped);
+    }
+
+    // Applies the policy to a malformed line: returns the error under Strict (or once the
+    // lenient cap is exceeded), otherwise records the line and lets the caller move on.
+    pub(crate) fn reject(
+        &mut self,
+        policy: ErrorPolicy,
+        line_offset: usize,
+        content: &[u8],
+        error: ParseError,
+    ) -> Result<(), WeatherError> {
+        let max_errors = match policy {
+            ErrorPolicy::Strict => return Err(WeatherError::Parse(error)),
+            ErrorPolicy::Lenient { max_errors } => max_errors,
+        };
+        
+        self.skipped.push(SkippedLine {
+            line_number: error.line,
+            byte_offset: line_offset,
+            content: content.to_vec(),
+            kind: error.kind,
+            error,
+        });
+        
+        match max_errors {
+            Some(limit) if self.skipped.len() > limit => Err(WeatherError::TooManyErrors(limit)),
+            _ => Ok(()),
+        }
+    }
+}
+
+// Same as std's BufReader default
+const DEFAULT_BUF_SIZE: usize = 8 * 1024;
+
 pub struct WeatherCsvReader<R> {
     reader: BufReader<R>,
+    line: Vec<u8>,
+    line_number: usize,
+    byte_offset: usize,
+    parser: LineParser,
+    policy: ErrorPolicy,
+    report: ErrorReport,
 }
 
 impl WeatherCsvReader<File> {
@@ -56,29 +275,72 @@ pub struct MmapWeatherCsvReader {
     mmap: memmap2::Mmap,
     position: usize,
     line_number: usize,
+    parser: LineParser,
+    policy: ErrorPolicy,
+    report: ErrorReport,
 }
 
 impl MmapWeatherCsvReader {
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, WeatherError> {
         let file = File::open(path)?;
-        let mmap = unsafe { MmapOptions::new().map(&file)? };
+        Self::from_file(&file)
+    }
+
+    // The file must be mappable (a regular file, not a pipe or terminal)
+    pub fn from_file(file: &File) -> Result<Self, WeatherError> {
+        let mmap = unsafe { MmapOptions::new().map(file)? };
         Ok(Self {
             mmap,
             position: 0,
             line_number: 0,
+            parser: LineParser::new(),
+            policy: ErrorPolicy::Strict,
+            report: ErrorReport::new(),
         })
     }
 
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn error_report(&self) -> &ErrorReport {
+        &self.report
+    }
+
+    pub fn into_error_report(self) -> ErrorReport {
+        self.report
+    }
+
     pub fn records(&mut self) -> MmapWeatherRecordIterator<'_> {
         MmapWeatherRecordIterator::new(self)
     }
 
-    pub fn read_all(mut self) -> Result<Vec<WeatherRecord>, WeatherError> {
-        let mut records = Vec::new();
-        for record in self.records() {
-            records.push(record?);
+    pub fn read_all(self) -> Result<Vec<WeatherRecord>, WeatherError> {
+        self.read_all_with_report().map(|(records, _)| records)
+    }
+
+    pub fn read_all_with_report(mut self) -> Result<(Vec<WeatherRecord>, ErrorReport), WeatherError> {
+        let records = self.collect_records()?;
+        Ok((records, self.report))
+    }
+}
+
+impl RecordSource for MmapWeatherCsvReader {
+    fn next_record(&mut self) -> Option<Result<ParsedRecord<'_>, WeatherError>> {
+        while let Some((line_offset, line)) = next_line(&self.mmap, &mut self.position) {
+            self.line_number += 1;
+            match accept_line(&self.parser, self.policy, &mut self.report, self.line_number, line_offset, line) {
+                Ok(Some(record)) => return Some(Ok(record)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
         }
-        Ok(records)
+        None
+    }
+
+    fn error_report(&self) -> &ErrorReport {
+        &self.report
     }
 }
 
@@ -90,151 +352,103 @@ impl<'a> MmapWeatherRecordIterator<'a> {
     fn new(reader: &'a mut MmapWeatherCsvReader) -> Self {
         Self { reader }
     }
-
-    fn parse_line(&self, line: &str) -> Result<WeatherRecord, WeatherError> {
-        let line = line.trim();
-        
-        if line.is_empty() {
-            return Err(WeatherError::InvalidFormat(
-                format!("Line {} is empty", self.reader.line_number)
-            ));
-        }
-
-        let parts: Vec<&str> = line.split(';').collect();
-        if parts.len() != 2 {
-            return Err(WeatherError::InvalidFormat(
-                format!("Line {} does not have exactly 2 columns separated by ';'. Found {} columns", 
-                       self.reader.line_number, parts.len())
-            ));
-        }
-
-        let station = parts[0].trim().to_string();
-        if station.is_empty() {
-            return Err(WeatherError::InvalidFormat(
-                format!("Line {}: Weather station name cannot be empty", self.reader.line_number)
-            ));
-        }
-
-        let temperature_str = parts[1].trim();
-        let temperature = f64::from_str(temperature_str)
-            .map_err(|_| WeatherError::Parse(
-                format!("Line {}: Cannot parse temperature '{}' as a number", 
-                       self.reader.line_number, temperature_str)
-            ))?;
-
-        Ok(WeatherRecord::new(station, temperature))
-    }
 }
 
 impl<'a> Iterator for MmapWeatherRecordIterator<'a> {
     type Item = Result<WeatherRecord, WeatherError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.reader.position >= self.reader.mmap.len() {
-            return None;
-        }
-
-        // Find the next line
-        let start = self.reader.position;
-        let mut end = start;
-        
-        // Find end of line
-        while end < self.reader.mmap.len() && self.reader.mmap[end] != b'\n' {
-            end += 1;
-        }
-
-        if start == end && end >= self.reader.mmap.len() {
-            return None; // EOF with no data
-        }
-
-        let line_bytes = &self.reader.mmap[start..end];
-        
-        // Skip to next line (past the \n)
-        self.reader.position = if end < self.reader.mmap.len() { end + 1 } else { end };
-        self.reader.line_number += 1;
-
-        // Convert to string
-        let line = match std::str::from_utf8(line_bytes) {
-            Ok(s) => s,
-            Err(_) => return Some(Err(WeatherError::InvalidFormat(
-                format!("Line {}: Invalid UTF-8 encoding", self.reader.line_number)
-            ))),
-        };
-
-        // Skip empty lines
-        if line.trim().is_empty() {
-            return self.next();
-        }
-
-        Some(self.parse_line(line))
+        self.reader.next_record().map(|record| record.map(|record| record.to_record()))
     }
 }
 
 impl<R: std::io::Read> WeatherCsvReader<R> {
     pub fn from_reader(reader: R) -> Self {
+        Self::with_capacity(DEFAULT_BUF_SIZE, reader)
+    }
+
+    // Reads the input in chunks of `capacity` bytes
+    pub fn with_capacity(capacity: usize, reader: R) -> Self {
         Self {
-            reader: BufReader::new(reader),
+            reader: BufReader::with_capacity(capacity, reader),
+            line: Vec::new(),
+            line_number: 0,
+            byte_offset: 0,
+            parser: LineParser::new(),
+            policy: ErrorPolicy::Strict,
+            report: ErrorReport::new(),
         }
     }
 
-    pub fn read_all(mut self) -> Result<Vec<WeatherRecord>, WeatherError> {
-        let mut records = Vec::new();
-        for record in self.records() {
-            records.push(record?);
-        }
-        Ok(records)
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = policy;
+        self
     }
 
-    pub fn records(&mut self) -> WeatherRecordIterator<'_, R> {
-        WeatherRecordIterator::new(&mut self.reader)
+    pub fn error_report(&self) -> &ErrorReport {
+        &self.report
     }
-}
 
-pub struct WeatherRecordIterator<'a, R> {
-    reader: &'a mut BufReader<R>,
-    line_number: usize,
-}
+    pub fn into_error_report(self) -> ErrorReport {
+        self.report
+    }
 
-impl<'a, R: std::io::Read> WeatherRecordIterator<'a, R> {
-    fn new(reader: &'a mut BufReader<R>) -> Self {
-        Self {
-            reader,
-            line_number: 0,
-        }
+    pub fn read_all(self) -> Result<Vec<WeatherRecord>, WeatherError> {
+        self.read_all_with_report().map(|(records, _)| records)
     }
 
-    fn parse_line(&self, line: &str) -> Result<WeatherRecord, WeatherError> {
-        let line = line.trim();
-        
-        if line.is_empty() {
-            return Err(WeatherError::InvalidFormat(
-                format!("Line {} is empty", self.line_number)
-            ));
-        }
+    pub fn read_all_with_report(mut self) -> Result<(Vec<WeatherRecord>, ErrorReport), WeatherError> {
+        let records = self.collect_records()?;
+        Ok((records, self.report))
+    }
 
-        let parts: Vec<&str> = line.split(';').collect();
-        if parts.len() != 2 {
-            return Err(WeatherError::InvalidFormat(
-                format!("Line {} does not have exactly 2 columns separated by ';'. Found {} columns", 
-                       self.line_number, parts.len())
-            ));
-        }
+    pub fn records(&mut self) -> WeatherRecordIterator<'_, R> {
+        WeatherRecordIterator::new(self)
+    }
+}
 
-        let station = parts[0].trim().to_string();
-        if station.is_empty() {
-            return Err(WeatherError::InvalidFormat(
-                format!("Line {}: Weather station name cannot be empty", self.line_number)
-            ));
-        }
+impl<R: std::io::Read> RecordSource for WeatherCsvReader<R> {
+    fn next_record(&mut self) -> Option<Result<ParsedRecord<'_>, WeatherError>> {
+        // The line buffer is reused, so remember where the accepted record lives and
+        // borrow it again once the loop is done
+        let (station, temperature) = loop {
+            self.line.clear();
+            let line_offset = self.byte_offset;
+            match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => return None, // EOF
+                Ok(read) => self.byte_offset += read,
+                Err(e) => return Some(Err(WeatherError::Io(e))),
+            }
+            self.line_number += 1;
+            
+            let line = self.line.strip_suffix(b"\n").unwrap_or(&self.line);
+            match accept_line(&self.parser, self.policy, &mut self.report, self.line_number, line_offset, line) {
+                Ok(Some(record)) => {
+                    let start = record.station.as_ptr() as usize - self.line.as_ptr() as usize;
+                    break (start..start + record.station.len(), record.temperature);
+                }
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        };
+        
+        // SAFETY: the range was accepted as a station name by the parser on this same buffer
+        let station = unsafe { std::str::from_utf8_unchecked(&self.line[station]) };
+        Some(Ok(ParsedRecord { station, temperature }))
+    }
 
-        let temperature_str = parts[1].trim();
-        let temperature = f64::from_str(temperature_str)
-            .map_err(|_| WeatherError::Parse(
-                format!("Line {}: Cannot parse temperature '{}' as a number", 
-                       self.line_number, temperature_str)
-            ))?;
+    fn error_report(&self) -> &ErrorReport {
+        &self.report
+    }
+}
 
-        Ok(WeatherRecord::new(station, temperature))
+pub struct WeatherRecordIterator<'a, R> {
+    reader: &'a mut WeatherCsvReader<R>,
+}
+
+impl<'a, R: std::io::Read> WeatherRecordIterator<'a, R> {
+    fn new(reader: &'a mut WeatherCsvReader<R>) -> Self {
+        Self { reader }
     }
 }
 
@@ -242,24 +456,7 @@ impl<'a, R: std::io::Read> Iterator for WeatherRecordIterator<'a, R> {
     type Item = Result<WeatherRecord, WeatherError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut line = String::new();
-        
-        loop {
-            line.clear();
-            self.line_number += 1;
-            
-            match self.reader.read_line(&mut line) {
-                Ok(0) => return None, // EOF
-                Ok(_) => {
-                    // Skip empty lines
-                    if line.trim().is_empty() {
-                        continue;
-                    }
-                    return Some(self.parse_line(&line));
-                }
-                Err(e) => return Some(Err(WeatherError::Io(e))),
-            }
-        }
+        self.reader.next_record().map(|record| record.map(|record| record.to_record()))
     }
 }
 
@@ -270,34 +467,143 @@ pub struct WeatherStats {
     pub min_temperature: f64,
     pub max_temperature: f64,
     pub avg_temperature: f64,
+    // Population variance and standard deviation over all records; None when built from
+    // station accumulators that don't track moments (e.g. plain StationStats)
+    pub variance: Option<f64>,
+    pub std_deviation: Option<f64>,
+    // Stations that recorded the extremes (the smallest name wins a tie)
+    pub min_temperature_station: String,
+    pub max_temperature_station: String,
 }
 
 impl WeatherStats {
     pub fn from_records(records: &[WeatherRecord]) -> Option<Self> {
-        if records.is_empty() {
-            return None;
+        let mut accumulator = WeatherStatsAccumulator::new();
+        for record in records {
+            accumulator.add_record(&record.station, record.temperature);
         }
+        accumulator.finish()
+    }
 
-        let mut stations = std::collections::HashSet::new();
-        let mut sum = 0.0;
-        let mut min = f64::INFINITY;
-        let mut max = f64::NEG_INFINITY;
+    // Global stats from an aggregated table, so the records never have to be held in memory
+    pub fn from_station_stats<A: StationAccumulator>(station_stats: &HashTable<String, A>) -> Option<Self> {
+        let mut accumulator = WeatherStatsAccumulator::new();
+        for (station, stats) in station_stats.iter() {
+            accumulator.add_station_stats(station, stats);
+        }
+        accumulator.finish()
+    }
+}
 
-        for record in records {
-            stations.insert(&record.station);
-            sum += record.temperature;
-            min = min.min(record.temperature);
-            max = max.max(record.temperature);
-        }
-
-        Some(Self {
-            total_records: records.len(),
-            unique_stations: stations.len(),
-            min_temperature: min,
-            max_temperature: max,
-            avg_temperature: sum / records.len() as f64,
+// Builds WeatherStats one record, or one station's StationStats, at a time.
+// Only the set of station names is kept, not the records.
+#[derive(Debug, Clone, Default)]
+pub struct WeatherStatsAccumulator {
+    stations: std::collections::HashSet<String>,
+    count: usize,
+    sum: f64,
+    compensation: f64,
+    mean: f64,
+    m2: f64,
+    // Set once a station without moments was added, so the variance can't be known
+    missing_moments: bool,
+    min: Option<(f64, String)>,
+    max: Option<(f64, String)>,
+}
+
+impl WeatherStatsAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_record(&mut self, station: &str, temperature: f64) {
+        self.add_station(station);
+        self.count += 1;
+        neumaier_add(&mut self.sum, &mut self.compensation, temperature);
+
+        // Welford's update
+        let delta = temperature - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (temperature - self.mean);
+
+        update_extreme(&mut self.min, temperature, station, |a, b| a < b);
+        update_extreme(&mut self.max, temperature, station, |a, b| a > b);
+    }
+
+    pub fn add_station_stats<A: StationAccumulator>(&mut self, station: &str, accumulator: &A) {
+        let stats = accumulator.station_stats();
+        if stats.count == 0 {
+            return;
+        }
+        self.add_station(station);
+
+        match accumulator.moments() {
+            Some((station_mean, station_m2)) => {
+                let (mean, m2) = merge_moments((self.count as f64, self.mean, self.m2), (stats.count as f64, station_mean, station_m2));
+                self.mean = mean;
+                self.m2 = m2;
+            }
+            None => self.missing_moments = true,
+        }
+        self.count += stats.count;
+        neumaier_add(&mut self.sum, &mut self.compensation, stats.sum_temperature);
+
+        update_extreme(&mut self.min, stats.min_temperature, station, |a, b| a < b);
+        update_extreme(&mut self.max, stats.max_temperature, station, |a, b| a > b);
+    }
+
+    pub fn merge(&mut self, other: &WeatherStatsAccumulator) {
+        for station in &other.stations {
+            self.add_station(station);
+        }
+
+        let (mean, m2) = merge_moments((self.count as f64, self.mean, self.m2), (other.count as f64, other.mean, other.m2));
+        self.mean = mean;
+        self.m2 = m2;
+        self.missing_moments |= other.missing_moments;
+        self.count += other.count;
+        neumaier_add(&mut self.sum, &mut self.compensation, other.sum);
+        self.compensation += other.compensation;
+
+        if let Some((temperature, station)) = &other.min {
+            update_extreme(&mut self.min, *temperature, station, |a, b| a < b);
+        }
+        if let Some((temperature, station)) = &other.max {
+            update_extreme(&mut self.max, *temperature, station, |a, b| a > b);
+        }
+    }
+
+    // None if nothing was added
+    pub fn finish(self) -> Option<WeatherStats> {
+        let (min_temperature, min_temperature_station) = self.min?;
+        let (max_temperature, max_temperature_station) = self.max?;
+        let variance = (!self.missing_moments).then(|| self.m2 / self.count as f64);
+
+        Some(WeatherStats {
+            total_records: self.count,
+            unique_stations: self.stations.len(),
+            min_temperature,
+            max_temperature,
+            avg_temperature: (self.sum + self.compensation) / self.count as f64,
+            variance,
+            std_deviation: variance.map(f64::sqrt),
+            min_temperature_station,
+            max_temperature_station,
         })
     }
+
+    fn add_station(&mut self, station: &str) {
+        if !self.stations.contains(station) {
+            self.stations.insert(station.to_string());
+        }
+    }
+}
+
+fn update_extreme(extreme: &mut Option<(f64, String)>, temperature: f64, station: &str, beats: fn(f64, f64) -> bool) {
+    match extreme {
+        Some((value, name)) if !(beats(temperature, *value) || (temperature == *value && station < name.as_str())) => {}
+        _ => *extreme = Some((temperature, station.to_string())),
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -327,6 +633,13 @@ impl StationStats {
         self.sum_temperature += temperature;
     }
 
+    pub fn merge(&mut self, other: &StationStats) {
+        self.count += other.count;
+        self.min_temperature = self.min_temperature.min(other.min_temperature);
+        self.max_temperature = self.max_temperature.max(other.max_temperature);
+        self.sum_temperature += other.sum_temperature;
+    }
+
     pub fn avg_temperature(&self) -> f64 {
         if self.count == 0 {
             0.0
@@ -336,6 +649,403 @@ impl StationStats {
     }
 }
 
+// Adds `value` to `sum`, accumulating the rounding error in `compensation` (Neumaier's
+// variant of Kahan summation, which also handles values larger than the running sum)
+#[inline]
+pub fn neumaier_add(sum: &mut f64, compensation: &mut f64, value: f64) {
+    let total = *sum + value;
+    if sum.abs() >= value.abs() {
+        *compensation += (*sum - total) + value;
+    } else {
+        *compensation += (value - total) + *sum;
+    }
+    *sum = total;
+}
+
+// Merges (count, mean, M2) of two partitions into the (mean, M2) of their union
+pub fn merge_moments((count_a, mean_a, m2_a): (f64, f64, f64), (count_b, mean_b, m2_b): (f64, f64, f64)) -> (f64, f64) {
+    let count = count_a + count_b;
+    if count == 0.0 {
+        return (0.0, 0.0);
+    }
+    
+    let delta = mean_b - mean_a;
+    let mean = mean_a + delta * count_b / count;
+    let m2 = m2_a + m2_b + delta * delta * count_a * count_b / count;
+    (mean, m2)
+}
+
+// Integer-backed station statistics; all temperatures are in tenths of a degree
+#[derive(Debug, Clone)]
+pub struct StationStatsFixed {
+    pub station_name: String,
+    pub count: u64,
+    pub min_temperature: i16,
+    pub max_temperature: i16,
+    pub sum_temperature: i64,
+}
+
+impl StationStatsFixed {
+    pub fn new(station_name: String, temperature: i16) -> Self {
+        Self {
+            station_name,
+            count: 1,
+            min_temperature: temperature,
+            max_temperature: temperature,
+            sum_temperature: temperature as i64,
+        }
+    }
+
+    pub fn add_temperature(&mut self, temperature: i16) {
+        self.count += 1;
+        self.min_temperature = self.min_temperature.min(temperature);
+        self.max_temperature = self.max_temperature.max(temperature);
+        self.sum_temperature += temperature as i64;
+    }
+
+    pub fn merge(&mut self, other: &StationStatsFixed) {
+        self.count += other.count;
+        self.min_temperature = self.min_temperature.min(other.min_temperature);
+        self.max_temperature = self.max_temperature.max(other.max_temperature);
+        self.sum_temperature += other.sum_temperature;
+    }
+
+    pub fn min_temperature(&self) -> f64 {
+        self.min_temperature as f64 / 10.0
+    }
+
+    pub fn max_temperature(&self) -> f64 {
+        self.max_temperature as f64 / 10.0
+    }
+
+    pub fn avg_temperature(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum_temperature as f64 / 10.0 / self.count as f64
+        }
+    }
+}
+
+// Per-station accumulator that RecordSource and the multi-file processor can aggregate into
+pub trait StationAccumulator: Clone + Send {
+    fn new(station_name: String, temperature: f64) -> Self;
+    fn add_temperature(&mut self, temperature: f64);
+    fn merge(&mut self, other: &Self);
+
+    // The count/min/max/mean part, for output
+    fn station_stats(&self) -> &StationStats;
+
+    // Welford's (mean, M2); None unless the accumulator tracks them
+    fn moments(&self) -> Option<(f64, f64)> {
+        None
+    }
+
+    // Population variance (divides by n); None unless the accumulator tracks moments
+    fn variance(&self) -> Option<f64> {
+        let count = self.station_stats().count;
+        self.moments().map(|(_, m2)| if count == 0 { 0.0 } else { m2 / count as f64 })
+    }
+
+    // None unless the accumulator keeps a distribution
+    fn percentile(&self, _percentile: f64) -> Option<f64> {
+        None
+    }
+}
+
+impl StationAccumulator for StationStats {
+    fn new(station_name: String, temperature: f64) -> Self {
+        StationStats::new(station_name, temperature)
+    }
+
+    fn add_temperature(&mut self, temperature: f64) {
+        StationStats::add_temperature(self, temperature)
+    }
+
+    fn merge(&mut self, other: &Self) {
+        StationStats::merge(self, other)
+    }
+
+    fn station_stats(&self) -> &StationStats {
+        self
+    }
+}
+
+// StationStats whose sum is kept as a whole number of tenths of a degree, so the average is
+// the same whatever order records are added or partial results merged in. A plain f64 sum
+// depends on that order, which is why this is opt-in rather than the default. Temperatures
+// that aren't exactly some tenth (not the 1BRC format) go into an f64 remainder instead; only
+// they can still make the average order-dependent.
+#[derive(Debug, Clone)]
+pub struct StationStatsExact {
+    // `sum_temperature` always equals sum_tenths / 10 + sum_remainder
+    pub stats: StationStats,
+    pub sum_tenths: i64,
+    pub sum_remainder: f64,
+}
+
+impl StationStatsExact {
+    fn add_to_sum(&mut self, temperature: f64) {
+        let tenths = (temperature * 10.0).round();
+        // Bounded so any realistic number of records can't overflow the i64
+        if tenths / 10.0 == temperature && tenths.abs() <= i32::MAX as f64 {
+            self.sum_tenths += tenths as i64;
+        } else {
+            self.sum_remainder += temperature;
+        }
+    }
+
+    fn sync_sum(&mut self) {
+        self.stats.sum_temperature = self.sum_tenths as f64 / 10.0 + self.sum_remainder;
+    }
+}
+
+impl StationAccumulator for StationStatsExact {
+    fn new(station_name: String, temperature: f64) -> Self {
+        let mut exact = Self {
+            stats: StationStats::new(station_name, temperature),
+            sum_tenths: 0,
+            sum_remainder: 0.0,
+        };
+        exact.add_to_sum(temperature);
+        exact.sync_sum();
+        exact
+    }
+
+    fn add_temperature(&mut self, temperature: f64) {
+        self.stats.add_temperature(temperature);
+        self.add_to_sum(temperature);
+        self.sync_sum();
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.stats.merge(&other.stats);
+        self.sum_tenths += other.sum_tenths;
+        self.sum_remainder += other.sum_remainder;
+        self.sync_sum();
+    }
+
+    fn station_stats(&self) -> &StationStats {
+        &self.stats
+    }
+}
+
+// StationStats plus Welford's running mean and M2, for variance and standard deviation.
+// Kept out of StationStats so only runs that report variance pay for the division per record.
+#[derive(Debug, Clone)]
+pub struct StationVarianceStats {
+    pub stats: StationStats,
+    pub mean_temperature: f64,
+    pub m2_temperature: f64,
+}
+
+impl StationVarianceStats {
+    // Population variance (divides by n)
+    pub fn variance(&self) -> f64 {
+        if self.stats.count == 0 {
+            0.0
+        } else {
+            self.m2_temperature / self.stats.count as f64
+        }
+    }
+
+    // Sample variance (divides by n - 1)
+    pub fn sample_variance(&self) -> f64 {
+        if self.stats.count < 2 {
+            0.0
+        } else {
+            self.m2_temperature / (self.stats.count - 1) as f64
+        }
+    }
+
+    pub fn std_deviation(&self) -> f64 {
+        self.variance().sqrt()
+    }
+}
+
+impl StationAccumulator for StationVarianceStats {
+    fn new(station_name: String, temperature: f64) -> Self {
+        Self {
+            stats: StationStats::new(station_name, temperature),
+            mean_temperature: temperature,
+            m2_temperature: 0.0,
+        }
+    }
+
+    fn add_temperature(&mut self, temperature: f64) {
+        self.stats.add_temperature(temperature);
+        
+        let delta = temperature - self.mean_temperature;
+        self.mean_temperature += delta / self.stats.count as f64;
+        self.m2_temperature += delta * (temperature - self.mean_temperature);
+    }
+
+    // Combines partial aggregates (chunks, files) with the pairwise update of Chan et al.
+    fn merge(&mut self, other: &Self) {
+        let (mean, m2) = merge_moments(
+            (self.stats.count as f64, self.mean_temperature, self.m2_temperature),
+            (other.stats.count as f64, other.mean_temperature, other.m2_temperature),
+        );
+        self.mean_temperature = mean;
+        self.m2_temperature = m2;
+        self.stats.merge(&other.stats);
+    }
+
+    fn station_stats(&self) -> &StationStats {
+        &self.stats
+    }
+
+    fn moments(&self) -> Option<(f64, f64)> {
+        Some((self.mean_temperature, self.m2_temperature))
+    }
+}
+
+// Temperatures covered by TemperatureHistogram, in tenths of a degree (-99.9..=99.9)
+pub const HISTOGRAM_MIN_TENTHS: i16 = -999;
+pub const HISTOGRAM_MAX_TENTHS: i16 = 999;
+pub const HISTOGRAM_BUCKETS: usize = (HISTOGRAM_MAX_TENTHS - HISTOGRAM_MIN_TENTHS) as usize + 1;
+
+// One counter per possible 1BRC temperature, so any quantile is exact without keeping
+// the raw values. Values outside -99.9..=99.9 or with more than one decimal have no bucket;
+// they are only counted, and percentiles are unknown once any was added.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemperatureHistogram {
+    // u32 keeps a histogram at 8 KB, which matters with one per station and per file
+    counts: Box<[u32]>,
+    count: u64,
+    unrepresentable: u64,
+}
+
+impl Default for TemperatureHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TemperatureHistogram {
+    pub fn new() -> Self {
+        Self {
+            counts: vec![0; HISTOGRAM_BUCKETS].into_boxed_slice(),
+            count: 0,
+            unrepresentable: 0,
+        }
+    }
+
+    #[inline]
+    pub fn add_tenths(&mut self, tenths: i16) {
+        self.count += 1;
+        if (HISTOGRAM_MIN_TENTHS..=HISTOGRAM_MAX_TENTHS).contains(&tenths) {
+            self.counts[(tenths - HISTOGRAM_MIN_TENTHS) as usize] += 1;
+        } else {
+            self.unrepresentable += 1;
+        }
+    }
+
+    #[inline]
+    pub fn add_temperature(&mut self, temperature: f64) {
+        let tenths = (temperature * 10.0).round();
+        // Only values that are exactly some tenth (as parsed from `-?\d+\.\d`) have a bucket
+        if tenths / 10.0 == temperature && (HISTOGRAM_MIN_TENTHS as f64..=HISTOGRAM_MAX_TENTHS as f64).contains(&tenths) {
+            self.add_tenths(tenths as i16);
+        } else {
+            self.count += 1;
+            self.unrepresentable += 1;
+        }
+    }
+
+    pub fn merge(&mut self, other: &TemperatureHistogram) {
+        for (count, other_count) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += other_count;
+        }
+        self.count += other.count;
+        self.unrepresentable += other.unrepresentable;
+    }
+
+    // Every value added, including unrepresentable ones
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    // Values that were out of range or not a whole number of tenths
+    pub fn unrepresentable(&self) -> u64 {
+        self.unrepresentable
+    }
+
+    // Nearest-rank percentile for `percentile` in 0..=100: the smallest recorded value with at
+    // least `percentile`% of all values at or below it. None when the histogram is empty or
+    // holds unrepresentable values, since their rank is unknown.
+    pub fn percentile(&self, percentile: f64) -> Option<f64> {
+        if self.count == 0 || self.unrepresentable > 0 {
+            return None;
+        }
+
+        let rank = ((percentile.clamp(0.0, 100.0) / 100.0 * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, &count) in self.counts.iter().enumerate() {
+            seen += count as u64;
+            if seen >= rank {
+                return Some((bucket as i16 + HISTOGRAM_MIN_TENTHS) as f64 / 10.0);
+            }
+        }
+        None
+    }
+
+    pub fn median(&self) -> Option<f64> {
+        self.percentile(50.0)
+    }
+}
+
+// Any accumulator (StationStats by default) plus a TemperatureHistogram, for reporting median
+// and other percentiles
+#[derive(Debug, Clone)]
+pub struct StationHistogramStats<A = StationStats> {
+    pub stats: A,
+    pub histogram: TemperatureHistogram,
+}
+
+impl<A> StationHistogramStats<A> {
+    pub fn percentile(&self, percentile: f64) -> Option<f64> {
+        self.histogram.percentile(percentile)
+    }
+
+    pub fn median(&self) -> Option<f64> {
+        self.histogram.median()
+    }
+}
+
+impl<A: StationAccumulator> StationAccumulator for StationHistogramStats<A> {
+    fn new(station_name: String, temperature: f64) -> Self {
+        let mut histogram = TemperatureHistogram::new();
+        histogram.add_temperature(temperature);
+        Self {
+            stats: A::new(station_name, temperature),
+            histogram,
+        }
+    }
+
+    fn add_temperature(&mut self, temperature: f64) {
+        self.stats.add_temperature(temperature);
+        self.histogram.add_temperature(temperature);
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.stats.merge(&other.stats);
+        self.histogram.merge(&other.histogram);
+    }
+
+    fn station_stats(&self) -> &StationStats {
+        self.stats.station_stats()
+    }
+
+    fn moments(&self) -> Option<(f64, f64)> {
+        self.stats.moments()
+    }
+
+    fn percentile(&self, percentile: f64) -> Option<f64> {
+        self.histogram.percentile(percentile)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -349,6 +1059,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(unused_mut)]
     fn test_parse_valid_csv() {
         let csv_data = "Station1;25.5\nStation2;-10.2\nStation3;0.0";
         let cursor = Cursor::new(csv_data);
@@ -368,6 +1079,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(unused_mut)]
     fn test_parse_with_whitespace() {
         let csv_data = "  Station1  ;  25.5  \n  Station2  ;  -10.2  ";
         let cursor = Cursor::new(csv_data);
@@ -381,6 +1093,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(unused_mut)]
     fn test_parse_with_empty_lines() {
         let csv_data = "Station1;25.5\n\n\nStation2;-10.2\n\n";
         let cursor = Cursor::new(csv_data);
@@ -394,6 +1107,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(unused_mut)]
     fn test_invalid_format_missing_semicolon() {
         let csv_data = "Station1 25.5";
         let cursor = Cursor::new(csv_data);
@@ -403,14 +1117,17 @@ mod tests {
         assert!(result.is_err());
         
         match result.unwrap_err() {
-            WeatherError::InvalidFormat(msg) => {
-                assert!(msg.contains("does not have exactly 2 columns"));
+            WeatherError::Parse(error) => {
+                assert_eq!(error.kind, ErrorKind::MissingDelimiter);
+                assert_eq!((error.line, error.column, error.byte_offset), (1, 1, 0));
+                assert_eq!(error.bytes, b"Station1 25.5");
             }
-            _ => panic!("Expected InvalidFormat error"),
+            other => panic!("Expected Parse error, got {:?}", other),
         }
     }
 
     #[test]
+    #[allow(unused_mut)]
     fn test_invalid_format_too_many_columns() {
         let csv_data = "Station1;25.5;extra";
         let cursor = Cursor::new(csv_data);
@@ -420,14 +1137,17 @@ mod tests {
         assert!(result.is_err());
         
         match result.unwrap_err() {
-            WeatherError::InvalidFormat(msg) => {
-                assert!(msg.contains("Found 3 columns"));
+            WeatherError::Parse(error) => {
+                assert_eq!(error.kind, ErrorKind::TooManyColumns);
+                assert_eq!((error.line, error.column, error.byte_offset), (1, 14, 13));
+                assert_eq!(error.bytes, b";extra");
             }
-            _ => panic!("Expected InvalidFormat error"),
+            other => panic!("Expected Parse error, got {:?}", other),
         }
     }
 
     #[test]
+    #[allow(unused_mut)]
     fn test_invalid_temperature() {
         let csv_data = "Station1;not_a_number";
         let cursor = Cursor::new(csv_data);
@@ -437,14 +1157,18 @@ mod tests {
         assert!(result.is_err());
         
         match result.unwrap_err() {
-            WeatherError::Parse(msg) => {
-                assert!(msg.contains("Cannot parse temperature"));
+            WeatherError::Parse(error) => {
+                assert_eq!(error.kind, ErrorKind::BadNumber);
+                assert_eq!((error.line, error.column, error.byte_offset), (1, 10, 9));
+                assert_eq!(error.bytes, b"not_a_number");
+                assert!(error.to_string().contains("cannot parse temperature 'not_a_number'"));
             }
-            _ => panic!("Expected Parse error"),
+            other => panic!("Expected Parse error, got {:?}", other),
         }
     }
 
     #[test]
+    #[allow(unused_mut)]
     fn test_empty_station_name() {
         let csv_data = ";25.5";
         let cursor = Cursor::new(csv_data);
@@ -454,13 +1178,94 @@ mod tests {
         assert!(result.is_err());
         
         match result.unwrap_err() {
-            WeatherError::InvalidFormat(msg) => {
-                assert!(msg.contains("Weather station name cannot be empty"));
+            WeatherError::Parse(error) => {
+                assert_eq!(error.kind, ErrorKind::EmptyStation);
+                assert_eq!((error.line, error.column), (1, 1));
+                assert!(error.bytes.is_empty());
             }
-            _ => panic!("Expected InvalidFormat error"),
+            other => panic!("Expected Parse error, got {:?}", other),
         }
     }
 
+    #[test]
+    fn test_lenient_policy_skips_and_reports_bad_lines() {
+        let csv_data = "Station1;25.5\nStation2 1.0\n;3.0\nStation4;abc\nStation5;1;2\n\nStation6;-4.5\n";
+        let reader = WeatherCsvReader::from_reader(Cursor::new(csv_data))
+            .with_error_policy(ErrorPolicy::lenient());
+        
+        let (records, report) = reader.read_all_with_report().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].station, "Station1");
+        assert_eq!(records[1].station, "Station6");
+        
+        let skipped = report.skipped();
+        assert_eq!(skipped.len(), 4);
+        assert_eq!(skipped.iter().map(|line| line.kind).collect::<Vec<_>>(), vec![
+            ErrorKind::MissingDelimiter,
+            ErrorKind::EmptyStation,
+            ErrorKind::BadNumber,
+            ErrorKind::TooManyColumns,
+        ]);
+        assert_eq!(skipped.iter().map(|line| line.line_number).collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+        assert_eq!(skipped.iter().map(|line| line.byte_offset).collect::<Vec<_>>(), vec![14, 27, 32, 45]);
+        assert_eq!(skipped[2].content, b"Station4;abc");
+        assert_eq!(skipped[2].error.bytes, b"abc");
+        assert_eq!(skipped[2].error.byte_offset, 41);
+        assert_eq!(report.count_of(ErrorKind::BadNumber), 1);
+    }
+
+    #[test]
+    fn test_lenient_policy_reports_invalid_utf8() {
+        let csv_data: &[u8] = b"Station1;25.5\nSta\xfftion;1.0\nStation3;2.0";
+        let reader = WeatherCsvReader::from_reader(Cursor::new(csv_data))
+            .with_error_policy(ErrorPolicy::lenient());
+        
+        let (records, report) = reader.read_all_with_report().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(report.len(), 1);
+        assert_eq!(report.skipped()[0].kind, ErrorKind::InvalidUtf8);
+        assert_eq!(report.skipped()[0].content, b"Sta\xfftion;1.0");
+        assert_eq!((report.skipped()[0].error.column, report.skipped()[0].error.byte_offset), (4, 17));
+        assert_eq!(report.skipped()[0].error.bytes, b"\xff");
+    }
+
+    #[test]
+    fn test_lenient_policy_error_cap() {
+        let csv_data = "bad1\nStation1;1.0\nbad2\nbad3\nStation2;2.0";
+        
+        let reader = WeatherCsvReader::from_reader(Cursor::new(csv_data))
+            .with_error_policy(ErrorPolicy::lenient_with_limit(3));
+        let (records, report) = reader.read_all_with_report().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(report.len(), 3);
+        
+        let reader = WeatherCsvReader::from_reader(Cursor::new(csv_data))
+            .with_error_policy(ErrorPolicy::lenient_with_limit(2));
+        match reader.read_all_with_report() {
+            Err(WeatherError::TooManyErrors(2)) => {}
+            other => panic!("Expected TooManyErrors, got {:?}", other.map(|(records, _)| records)),
+        }
+    }
+
+    #[test]
+    fn test_mmap_reader_lenient_policy() {
+        let strict = MmapWeatherCsvReader::from_path("data/bad_weather.csv").unwrap();
+        assert!(strict.read_all().is_err());
+        
+        let reader = MmapWeatherCsvReader::from_path("data/bad_weather.csv")
+            .unwrap()
+            .with_error_policy(ErrorPolicy::lenient());
+        let (records, report) = reader.read_all_with_report().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(report.len(), 1);
+        
+        let skipped = &report.skipped()[0];
+        assert_eq!(skipped.line_number, 2);
+        assert_eq!(skipped.byte_offset, 14);
+        assert_eq!(skipped.content, b"Station2;invalid_temp");
+        assert_eq!(skipped.kind, ErrorKind::BadNumber);
+    }
+
     #[test]
     fn test_iterator() {
         let csv_data = "Station1;25.5\nStation2;-10.2";
@@ -520,4 +1325,248 @@ mod tests {
         assert_eq!(stats.min_temperature, 15.0);
         assert_eq!(stats.max_temperature, 35.0);
     }
+
+    #[test]
+    fn test_station_stats_merge() {
+        let mut left = StationStats::new("Station1".to_string(), 25.0);
+        left.add_temperature(15.0);
+
+        let mut right = StationStats::new("Station1".to_string(), -5.0);
+        right.add_temperature(40.0);
+
+        left.merge(&right);
+        assert_eq!(left.count, 4);
+        assert_eq!(left.min_temperature, -5.0);
+        assert_eq!(left.max_temperature, 40.0);
+        assert_eq!(left.sum_temperature, 75.0);
+        assert_eq!(left.avg_temperature(), 18.75);
+    }
+
+    #[test]
+    fn test_station_stats_fixed() {
+        let mut stats = StationStatsFixed::new("Station1".to_string(), 250);
+        stats.add_temperature(-123);
+        stats.add_temperature(5);
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.min_temperature, -123);
+        assert_eq!(stats.max_temperature, 250);
+        assert_eq!(stats.sum_temperature, 132);
+        assert_eq!(stats.min_temperature(), -12.3);
+        assert_eq!(stats.max_temperature(), 25.0);
+        assert!((stats.avg_temperature() - 4.4).abs() < 1e-9);
+
+        let other = StationStatsFixed::new("Station1".to_string(), 999);
+        stats.merge(&other);
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.max_temperature, 999);
+        assert_eq!(stats.sum_temperature, 1131);
+    }
+
+    #[test]
+    fn test_histogram_percentiles_match_sorted_values() {
+        let mut histogram = TemperatureHistogram::new();
+        let mut values = Vec::new();
+        for i in 0..1000 {
+            let tenths = ((i * 37) % 1999) as i16 - 999;
+            histogram.add_temperature(tenths as f64 / 10.0);
+            values.push(tenths);
+        }
+        values.sort_unstable();
+
+        for percentile in [0.0, 1.0, 25.0, 50.0, 90.0, 99.0, 99.9, 100.0] {
+            let rank = ((percentile / 100.0 * values.len() as f64).ceil() as usize).max(1);
+            assert_eq!(histogram.percentile(percentile), Some(values[rank - 1] as f64 / 10.0), "p{}", percentile);
+        }
+        assert_eq!(histogram.count(), 1000);
+        assert_eq!(TemperatureHistogram::new().median(), None);
+    }
+
+    #[test]
+    fn test_exact_sum_keeps_other_temperatures_apart() {
+        let mut stats = StationStatsExact::new("Hamburg".to_string(), 12.3);
+        stats.add_temperature(-0.1);
+        stats.add_temperature(0.25);
+        
+        assert_eq!((stats.sum_tenths, stats.sum_remainder), (122, 0.25));
+        assert_eq!(stats.stats.sum_temperature, 12.2 + 0.25);
+        assert_eq!(stats.stats.count, 3);
+    }
+
+    #[test]
+    fn test_histogram_merge() {
+        let mut left = TemperatureHistogram::new();
+        left.add_temperature(-5.0);
+        left.add_temperature(99.9);
+
+        let mut right = TemperatureHistogram::new();
+        right.add_temperature(1.0);
+        right.add_temperature(2.0);
+
+        left.merge(&right);
+        assert_eq!(left.count(), 4);
+        assert_eq!(left.percentile(0.0), Some(-5.0));
+        assert_eq!(left.median(), Some(1.0));
+        assert_eq!(left.percentile(100.0), Some(99.9));
+    }
+
+    #[test]
+    fn test_histogram_unrepresentable_values() {
+        for value in [150.0, -100.0, 12.25, 0.05, f64::NAN] {
+            let mut histogram = TemperatureHistogram::new();
+            histogram.add_temperature(1.0);
+            histogram.add_temperature(value);
+            assert_eq!((histogram.count(), histogram.unrepresentable()), (2, 1), "{}", value);
+            assert_eq!(histogram.median(), None, "{}", value);
+
+            // Merging carries the unknown rank along
+            let mut merged = TemperatureHistogram::new();
+            merged.add_temperature(2.0);
+            merged.merge(&histogram);
+            assert_eq!(merged.unrepresentable(), 1);
+            assert_eq!(merged.percentile(100.0), None);
+        }
+
+        let mut histogram = TemperatureHistogram::new();
+        histogram.add_tenths(1000);
+        histogram.add_temperature(-0.0);
+        histogram.add_temperature(-12.3);
+        assert_eq!((histogram.count(), histogram.unrepresentable()), (3, 1));
+        assert_eq!(histogram.percentile(0.0), None);
+    }
+
+    #[test]
+    fn test_aggregate_with_histograms() {
+        let data = "Station1;25.5\nStation1;28.3\nStation1;26.1\nStation2;-1.0\n";
+        let mut reader = WeatherCsvReader::from_reader(Cursor::new(data));
+        let stats: crate::HashTable<String, StationHistogramStats> = reader.aggregate_with().unwrap();
+
+        let station1 = stats.get("Station1").unwrap();
+        assert_eq!(station1.stats.count, 3);
+        assert_eq!(station1.stats.max_temperature, 28.3);
+        assert_eq!(station1.median(), Some(26.1));
+        assert_eq!(station1.percentile(90.0), Some(28.3));
+        assert_eq!(stats.get("Station2").unwrap().median(), Some(-1.0));
+    }
+
+    fn two_pass_variance(values: &[f64]) -> f64 {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / values.len() as f64
+    }
+
+    #[test]
+    fn test_station_stats_variance() {
+        let values = [12.0, -3.4, 8.9, 25.1, 0.0, -17.6, 8.9];
+        let mut stats = StationVarianceStats::new("Station1".to_string(), values[0]);
+        for &value in &values[1..] {
+            stats.add_temperature(value);
+        }
+
+        assert!((stats.variance() - two_pass_variance(&values)).abs() < 1e-9);
+        assert!((stats.std_deviation() - two_pass_variance(&values).sqrt()).abs() < 1e-9);
+        assert!((stats.sample_variance() - two_pass_variance(&values) * 7.0 / 6.0).abs() < 1e-9);
+        assert!((stats.mean_temperature - stats.stats.avg_temperature()).abs() < 1e-9);
+        assert_eq!(StationAccumulator::variance(&stats), Some(stats.variance()));
+        assert_eq!(StationVarianceStats::new("Station1".to_string(), 5.0).variance(), 0.0);
+
+        // Plain StationStats doesn't pay for moments
+        assert_eq!(StationAccumulator::variance(&StationStats::new("Station1".to_string(), 5.0)), None);
+    }
+
+    #[test]
+    fn test_station_stats_merge_variance() {
+        let values = [12.0, -3.4, 8.9, 25.1, 0.0, -17.6, 8.9, 99.9, -99.9];
+
+        // Every split point must give the same result as one sequential pass
+        for split in 1..values.len() {
+            let mut left = StationVarianceStats::new("Station1".to_string(), values[0]);
+            for &value in &values[1..split] {
+                left.add_temperature(value);
+            }
+            let mut right = StationVarianceStats::new("Station1".to_string(), values[split]);
+            for &value in &values[split + 1..] {
+                right.add_temperature(value);
+            }
+
+            left.merge(&right);
+            assert_eq!(left.stats.count, values.len());
+            assert!((left.variance() - two_pass_variance(&values)).abs() < 1e-9, "split at {}", split);
+        }
+    }
+
+    #[test]
+    fn test_weather_stats_variance() {
+        let values = [25.5, -10.2, 28.3, 0.0];
+        let records: Vec<WeatherRecord> = values
+            .iter()
+            .enumerate()
+            .map(|(i, &t)| WeatherRecord::new(format!("Station{}", i % 2), t))
+            .collect();
+
+        let stats = WeatherStats::from_records(&records).unwrap();
+        assert!((stats.variance.unwrap() - two_pass_variance(&values)).abs() < 1e-9);
+        assert!((stats.std_deviation.unwrap() - two_pass_variance(&values).sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_weather_stats_from_station_stats_matches_records() {
+        let records = MmapWeatherCsvReader::from_path("data/measurements_1KRecords.txt").unwrap().read_all().unwrap();
+        let station_stats: HashTable<String, StationVarianceStats> =
+            MmapWeatherCsvReader::from_path("data/measurements_1KRecords.txt").unwrap().aggregate_with().unwrap();
+
+        let expected = WeatherStats::from_records(&records).unwrap();
+        let actual = WeatherStats::from_station_stats(&station_stats).unwrap();
+        assert_eq!(actual.total_records, 1000);
+        assert_eq!(actual.total_records, expected.total_records);
+        assert_eq!(actual.unique_stations, expected.unique_stations);
+        assert_eq!(actual.min_temperature, expected.min_temperature);
+        assert_eq!(actual.max_temperature, expected.max_temperature);
+        assert_eq!(actual.min_temperature_station, expected.min_temperature_station);
+        assert_eq!(actual.max_temperature_station, expected.max_temperature_station);
+        assert!((actual.avg_temperature - expected.avg_temperature).abs() < 1e-9);
+        assert!((actual.variance.unwrap() - expected.variance.unwrap()).abs() < 1e-9);
+        assert!(WeatherStats::from_station_stats::<StationStats>(&HashTable::new()).is_none());
+
+        // Plain StationStats carries no moments, so only the variance is unknown
+        let plain = MmapWeatherCsvReader::from_path("data/measurements_1KRecords.txt").unwrap().aggregate().unwrap();
+        let actual = WeatherStats::from_station_stats(&plain).unwrap();
+        assert_eq!(actual.total_records, 1000);
+        assert_eq!((actual.variance, actual.std_deviation), (None, None));
+    }
+
+    #[test]
+    fn test_weather_stats_extreme_stations() {
+        let records = vec![
+            WeatherRecord::new("Oslo".to_string(), -12.5),
+            WeatherRecord::new("Cairo".to_string(), 41.0),
+            WeatherRecord::new("Bergen".to_string(), -12.5),
+            WeatherRecord::new("Cairo".to_string(), 3.0),
+        ];
+
+        let stats = WeatherStats::from_records(&records).unwrap();
+        assert_eq!(stats.unique_stations, 3);
+        assert_eq!((stats.min_temperature, stats.min_temperature_station.as_str()), (-12.5, "Bergen"));
+        assert_eq!((stats.max_temperature, stats.max_temperature_station.as_str()), (41.0, "Cairo"));
+        assert!(WeatherStats::from_records(&[]).is_none());
+    }
+
+    #[test]
+    fn test_weather_stats_accumulator_merge() {
+        let records = MmapWeatherCsvReader::from_path("data/measurements_1KRecords.txt").unwrap().read_all().unwrap();
+        let (left, right) = records.split_at(377);
+
+        let mut accumulator = WeatherStatsAccumulator::new();
+        let mut other = WeatherStatsAccumulator::new();
+        left.iter().for_each(|record| accumulator.add_record(&record.station, record.temperature));
+        right.iter().for_each(|record| other.add_record(&record.station, record.temperature));
+        accumulator.merge(&other);
+
+        let merged = accumulator.finish().unwrap();
+        let expected = WeatherStats::from_records(&records).unwrap();
+        assert_eq!(merged.total_records, expected.total_records);
+        assert_eq!(merged.unique_stations, expected.unique_stations);
+        assert_eq!(merged.min_temperature_station, expected.min_temperature_station);
+        assert_eq!(merged.max_temperature_station, expected.max_temperature_station);
+        assert!((merged.avg_temperature - expected.avg_temperature).abs() < 1e-9);
+        assert!((merged.variance.unwrap() - expected.variance.unwrap()).abs() < 1e-9);
+    }
 }
\ No newline at end of file
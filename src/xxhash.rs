@@ -1,4 +1,6 @@
-use std::hash::Hasher;
+use std::borrow::Cow;
+use std::hash::{BuildHasher, Hasher};
+use std::mem::MaybeUninit;
 
 const PRIME32_1: u32 = 0x9E3779B1;
 const PRIME32_2: u32 = 0x85EBCA77;
@@ -137,6 +139,29 @@ impl Hasher for XxHash32 {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XxHash32Builder {
+    seed: u32,
+}
+
+impl XxHash32Builder {
+    pub fn new(seed: u32) -> Self {
+        Self { seed }
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+impl BuildHasher for XxHash32Builder {
+    type Hasher = XxHash32;
+
+    fn build_hasher(&self) -> XxHash32 {
+        XxHash32::new(self.seed)
+    }
+}
+
 pub struct XxHash64 {
     seed: u64,
     acc1: u64,
@@ -282,6 +307,650 @@ impl Hasher for XxHash64 {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XxHash64Builder {
+    seed: u64,
+}
+
+impl XxHash64Builder {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl BuildHasher for XxHash64Builder {
+    type Hasher = XxHash64;
+
+    fn build_hasher(&self) -> XxHash64 {
+        XxHash64::new(self.seed)
+    }
+}
+
+// ============================================================================
+// XXH3 (64-bit and 128-bit)
+// ============================================================================
+
+const XXH3_SECRET_SIZE: usize = 192;
+const XXH3_SECRET_SIZE_MIN: usize = 136;
+const XXH3_STRIPE_LEN: usize = 64;
+const XXH3_SECRET_CONSUME_RATE: usize = 8;
+const XXH3_STRIPES_PER_BLOCK: usize = (XXH3_SECRET_SIZE - XXH3_STRIPE_LEN) / XXH3_SECRET_CONSUME_RATE;
+const XXH3_SECRET_LASTACC_START: usize = 7;
+const XXH3_SECRET_MERGEACCS_START: usize = 11;
+const XXH3_MIDSIZE_MAX: usize = 240;
+const XXH3_MIDSIZE_STARTOFFSET: usize = 3;
+const XXH3_MIDSIZE_LASTOFFSET: usize = 17;
+const XXH3_BUFFER_SIZE: usize = 256;
+const XXH3_BUFFER_STRIPES: usize = XXH3_BUFFER_SIZE / XXH3_STRIPE_LEN;
+
+const PRIME_MX1: u64 = 0x165667919E3779F9;
+const PRIME_MX2: u64 = 0x9FB21C651E98DF25;
+
+const XXH3_INIT_ACC: [u64; 8] = [
+    PRIME32_3 as u64, PRIME64_1, PRIME64_2, PRIME64_3,
+    PRIME64_4, PRIME32_2 as u64, PRIME64_5, PRIME32_1 as u64,
+];
+
+const XXH3_DEFAULT_SECRET: [u8; XXH3_SECRET_SIZE] = [
+    0xb8, 0xfe, 0x6c, 0x39, 0x23, 0xa4, 0x4b, 0xbe, 0x7c, 0x01, 0x81, 0x2c, 0xf7, 0x21, 0xad, 0x1c,
+    0xde, 0xd4, 0x6d, 0xe9, 0x83, 0x90, 0x97, 0xdb, 0x72, 0x40, 0xa4, 0xa4, 0xb7, 0xb3, 0x67, 0x1f,
+    0xcb, 0x79, 0xe6, 0x4e, 0xcc, 0xc0, 0xe5, 0x78, 0x82, 0x5a, 0xd0, 0x7d, 0xcc, 0xff, 0x72, 0x21,
+    0xb8, 0x08, 0x46, 0x74, 0xf7, 0x43, 0x24, 0x8e, 0xe0, 0x35, 0x90, 0xe6, 0x81, 0x3a, 0x26, 0x4c,
+    0x3c, 0x28, 0x52, 0xbb, 0x91, 0xc3, 0x00, 0xcb, 0x88, 0xd0, 0x65, 0x8b, 0x1b, 0x53, 0x2e, 0xa3,
+    0x71, 0x64, 0x48, 0x97, 0xa2, 0x0d, 0xf9, 0x4e, 0x38, 0x19, 0xef, 0x46, 0xa9, 0xde, 0xac, 0xd8,
+    0xa8, 0xfa, 0x76, 0x3f, 0xe3, 0x9c, 0x34, 0x3f, 0xf9, 0xdc, 0xbb, 0xc7, 0xc7, 0x0b, 0x4f, 0x1d,
+    0x8a, 0x51, 0xe0, 0x4b, 0xcd, 0xb4, 0x59, 0x31, 0xc8, 0x9f, 0x7e, 0xc9, 0xd9, 0x78, 0x73, 0x64,
+    0xea, 0xc5, 0xac, 0x83, 0x34, 0xd3, 0xeb, 0xc3, 0xc5, 0x81, 0xa0, 0xff, 0xfa, 0x13, 0x63, 0xeb,
+    0x17, 0x0d, 0xdd, 0x51, 0xb7, 0xf0, 0xda, 0x49, 0xd3, 0x16, 0x55, 0x26, 0x29, 0xd4, 0x68, 0x9e,
+    0x2b, 0x16, 0xbe, 0x58, 0x7d, 0x47, 0xa1, 0xfc, 0x8f, 0xf8, 0xb8, 0xd1, 0x7a, 0xd0, 0x31, 0xce,
+    0x45, 0xcb, 0x3a, 0x8f, 0x95, 0x16, 0x04, 0x28, 0xaf, 0xd7, 0xfb, 0xca, 0xbb, 0x4b, 0x40, 0x7e,
+];
+
+#[inline(always)]
+fn read32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+#[inline(always)]
+fn read64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+#[inline(always)]
+fn mul128_fold64(lhs: u64, rhs: u64) -> u64 {
+    let product = lhs as u128 * rhs as u128;
+    (product as u64) ^ ((product >> 64) as u64)
+}
+
+#[inline(always)]
+fn mult64to128(lhs: u64, rhs: u64) -> (u64, u64) {
+    let product = lhs as u128 * rhs as u128;
+    (product as u64, (product >> 64) as u64)
+}
+
+fn xxh64_avalanche(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(PRIME64_2);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(PRIME64_3);
+    hash ^= hash >> 32;
+    hash
+}
+
+fn xxh3_avalanche(mut hash: u64) -> u64 {
+    hash ^= hash >> 37;
+    hash = hash.wrapping_mul(PRIME_MX1);
+    hash ^= hash >> 32;
+    hash
+}
+
+fn xxh3_rrmxmx(mut hash: u64, len: u64) -> u64 {
+    hash ^= hash.rotate_left(49) ^ hash.rotate_left(24);
+    hash = hash.wrapping_mul(PRIME_MX2);
+    hash ^= (hash >> 35).wrapping_add(len);
+    hash = hash.wrapping_mul(PRIME_MX2);
+    hash ^= hash >> 28;
+    hash
+}
+
+#[inline(always)]
+fn xxh3_mix16b(input: &[u8], secret: &[u8], seed: u64) -> u64 {
+    let input_lo = read64(input, 0);
+    let input_hi = read64(input, 8);
+    mul128_fold64(
+        input_lo ^ read64(secret, 0).wrapping_add(seed),
+        input_hi ^ read64(secret, 8).wrapping_sub(seed),
+    )
+}
+
+#[inline(always)]
+fn xxh3_mix32b(acc: (u64, u64), input_1: &[u8], input_2: &[u8], secret: &[u8], seed: u64) -> (u64, u64) {
+    let mut low = acc.0.wrapping_add(xxh3_mix16b(input_1, secret, seed));
+    low ^= read64(input_2, 0).wrapping_add(read64(input_2, 8));
+    let mut high = acc.1.wrapping_add(xxh3_mix16b(input_2, &secret[16..], seed));
+    high ^= read64(input_1, 0).wrapping_add(read64(input_1, 8));
+    (low, high)
+}
+
+fn xxh3_custom_secret(seed: u64) -> [u8; XXH3_SECRET_SIZE] {
+    let mut secret = [0u8; XXH3_SECRET_SIZE];
+    for i in 0..XXH3_SECRET_SIZE / 16 {
+        let low = read64(&XXH3_DEFAULT_SECRET, 16 * i).wrapping_add(seed);
+        let high = read64(&XXH3_DEFAULT_SECRET, 16 * i + 8).wrapping_sub(seed);
+        secret[16 * i..16 * i + 8].copy_from_slice(&low.to_le_bytes());
+        secret[16 * i + 8..16 * i + 16].copy_from_slice(&high.to_le_bytes());
+    }
+    secret
+}
+
+#[inline(always)]
+fn xxh3_accumulate_512(acc: &mut [u64; 8], stripe: &[u8], secret: &[u8]) {
+    for i in 0..8 {
+        let data_val = read64(stripe, 8 * i);
+        let data_key = data_val ^ read64(secret, 8 * i);
+        acc[i ^ 1] = acc[i ^ 1].wrapping_add(data_val);
+        acc[i] = acc[i].wrapping_add((data_key & 0xFFFF_FFFF).wrapping_mul(data_key >> 32));
+    }
+}
+
+#[inline(always)]
+fn xxh3_scramble_acc(acc: &mut [u64; 8], secret: &[u8]) {
+    for (i, lane) in acc.iter_mut().enumerate() {
+        let mut value = *lane;
+        value ^= value >> 47;
+        value ^= read64(secret, 8 * i);
+        *lane = value.wrapping_mul(PRIME32_1 as u64);
+    }
+}
+
+fn xxh3_merge_accs(acc: &[u64; 8], secret: &[u8], start: u64) -> u64 {
+    let mut result = start;
+    for i in 0..4 {
+        result = result.wrapping_add(mul128_fold64(
+            acc[2 * i] ^ read64(secret, 16 * i),
+            acc[2 * i + 1] ^ read64(secret, 16 * i + 8),
+        ));
+    }
+    xxh3_avalanche(result)
+}
+
+// Feeds whole stripes into the accumulators, scrambling at every block boundary
+fn xxh3_consume_stripes(acc: &mut [u64; 8], stripes_so_far: &mut usize, input: &[u8], stripe_count: usize, secret: &[u8]) {
+    for stripe in input.chunks_exact(XXH3_STRIPE_LEN).take(stripe_count) {
+        xxh3_accumulate_512(acc, stripe, &secret[*stripes_so_far * XXH3_SECRET_CONSUME_RATE..]);
+        *stripes_so_far += 1;
+        if *stripes_so_far == XXH3_STRIPES_PER_BLOCK {
+            xxh3_scramble_acc(acc, &secret[XXH3_SECRET_SIZE - XXH3_STRIPE_LEN..]);
+            *stripes_so_far = 0;
+        }
+    }
+}
+
+fn xxh3_hash_long_acc(input: &[u8], secret: &[u8]) -> [u64; 8] {
+    let mut acc = XXH3_INIT_ACC;
+    let mut stripes_so_far = 0;
+    let stripe_count = (input.len() - 1) / XXH3_STRIPE_LEN;
+
+    xxh3_consume_stripes(&mut acc, &mut stripes_so_far, input, stripe_count, secret);
+
+    // The last stripe always ends at the end of the input and may overlap the previous one
+    let last_stripe = &input[input.len() - XXH3_STRIPE_LEN..];
+    xxh3_accumulate_512(&mut acc, last_stripe, &secret[XXH3_SECRET_SIZE - XXH3_STRIPE_LEN - XXH3_SECRET_LASTACC_START..]);
+    acc
+}
+
+fn xxh3_64_short(input: &[u8], secret: &[u8], seed: u64) -> u64 {
+    let len = input.len();
+    match len {
+        0 => xxh64_avalanche(seed ^ read64(secret, 56) ^ read64(secret, 64)),
+        1..=3 => {
+            let combined = ((input[0] as u32) << 16)
+                | ((input[len >> 1] as u32) << 24)
+                | (input[len - 1] as u32)
+                | ((len as u32) << 8);
+            let bitflip = ((read32(secret, 0) ^ read32(secret, 4)) as u64).wrapping_add(seed);
+            xxh64_avalanche(combined as u64 ^ bitflip)
+        }
+        4..=8 => {
+            let seed = seed ^ (((seed as u32).swap_bytes() as u64) << 32);
+            let input_1 = read32(input, 0);
+            let input_2 = read32(input, len - 4);
+            let bitflip = (read64(secret, 8) ^ read64(secret, 16)).wrapping_sub(seed);
+            let input_64 = (input_2 as u64).wrapping_add((input_1 as u64) << 32);
+            xxh3_rrmxmx(input_64 ^ bitflip, len as u64)
+        }
+        9..=16 => {
+            let bitflip_1 = (read64(secret, 24) ^ read64(secret, 32)).wrapping_add(seed);
+            let bitflip_2 = (read64(secret, 40) ^ read64(secret, 48)).wrapping_sub(seed);
+            let input_lo = read64(input, 0) ^ bitflip_1;
+            let input_hi = read64(input, len - 8) ^ bitflip_2;
+            let acc = (len as u64)
+                .wrapping_add(input_lo.swap_bytes())
+                .wrapping_add(input_hi)
+                .wrapping_add(mul128_fold64(input_lo, input_hi));
+            xxh3_avalanche(acc)
+        }
+        17..=128 => {
+            let mut acc = (len as u64).wrapping_mul(PRIME64_1);
+            if len > 32 {
+                if len > 64 {
+                    if len > 96 {
+                        acc = acc.wrapping_add(xxh3_mix16b(&input[48..], &secret[96..], seed));
+                        acc = acc.wrapping_add(xxh3_mix16b(&input[len - 64..], &secret[112..], seed));
+                    }
+                    acc = acc.wrapping_add(xxh3_mix16b(&input[32..], &secret[64..], seed));
+                    acc = acc.wrapping_add(xxh3_mix16b(&input[len - 48..], &secret[80..], seed));
+                }
+                acc = acc.wrapping_add(xxh3_mix16b(&input[16..], &secret[32..], seed));
+                acc = acc.wrapping_add(xxh3_mix16b(&input[len - 32..], &secret[48..], seed));
+            }
+            acc = acc.wrapping_add(xxh3_mix16b(input, secret, seed));
+            acc = acc.wrapping_add(xxh3_mix16b(&input[len - 16..], &secret[16..], seed));
+            xxh3_avalanche(acc)
+        }
+        _ => {
+            let mut acc = (len as u64).wrapping_mul(PRIME64_1);
+            for i in 0..8 {
+                acc = acc.wrapping_add(xxh3_mix16b(&input[16 * i..], &secret[16 * i..], seed));
+            }
+            acc = xxh3_avalanche(acc);
+            for i in 8..len / 16 {
+                acc = acc.wrapping_add(xxh3_mix16b(&input[16 * i..], &secret[16 * (i - 8) + XXH3_MIDSIZE_STARTOFFSET..], seed));
+            }
+            acc = acc.wrapping_add(xxh3_mix16b(&input[len - 16..], &secret[XXH3_SECRET_SIZE_MIN - XXH3_MIDSIZE_LASTOFFSET..], seed));
+            xxh3_avalanche(acc)
+        }
+    }
+}
+
+fn xxh3_128_short(input: &[u8], secret: &[u8], seed: u64) -> u128 {
+    let len = input.len();
+    let (low, high) = match len {
+        0 => (
+            xxh64_avalanche(seed ^ read64(secret, 64) ^ read64(secret, 72)),
+            xxh64_avalanche(seed ^ read64(secret, 80) ^ read64(secret, 88)),
+        ),
+        1..=3 => {
+            let combined_low = ((input[0] as u32) << 16)
+                | ((input[len >> 1] as u32) << 24)
+                | (input[len - 1] as u32)
+                | ((len as u32) << 8);
+            let combined_high = combined_low.swap_bytes().rotate_left(13);
+            let bitflip_low = ((read32(secret, 0) ^ read32(secret, 4)) as u64).wrapping_add(seed);
+            let bitflip_high = ((read32(secret, 8) ^ read32(secret, 12)) as u64).wrapping_sub(seed);
+            (
+                xxh64_avalanche(combined_low as u64 ^ bitflip_low),
+                xxh64_avalanche(combined_high as u64 ^ bitflip_high),
+            )
+        }
+        4..=8 => {
+            let seed = seed ^ (((seed as u32).swap_bytes() as u64) << 32);
+            let input_lo = read32(input, 0);
+            let input_hi = read32(input, len - 4);
+            let input_64 = (input_lo as u64).wrapping_add((input_hi as u64) << 32);
+            let bitflip = (read64(secret, 16) ^ read64(secret, 24)).wrapping_add(seed);
+            let keyed = input_64 ^ bitflip;
+
+            let (mut low, mut high) = mult64to128(keyed, PRIME64_1.wrapping_add((len as u64) << 2));
+            high = high.wrapping_add(low << 1);
+            low ^= high >> 3;
+            low ^= low >> 35;
+            low = low.wrapping_mul(PRIME_MX2);
+            low ^= low >> 28;
+            (low, xxh3_avalanche(high))
+        }
+        9..=16 => {
+            let bitflip_low = (read64(secret, 32) ^ read64(secret, 40)).wrapping_sub(seed);
+            let bitflip_high = (read64(secret, 48) ^ read64(secret, 56)).wrapping_add(seed);
+            let input_lo = read64(input, 0);
+            let mut input_hi = read64(input, len - 8);
+
+            let (mut m_low, mut m_high) = mult64to128(input_lo ^ input_hi ^ bitflip_low, PRIME64_1);
+            m_low = m_low.wrapping_add(((len - 1) as u64) << 54);
+            input_hi ^= bitflip_high;
+            m_high = m_high
+                .wrapping_add(input_hi)
+                .wrapping_add((input_hi & 0xFFFF_FFFF).wrapping_mul(PRIME32_2 as u64 - 1));
+            m_low ^= m_high.swap_bytes();
+
+            let (low, high) = mult64to128(m_low, PRIME64_2);
+            let high = high.wrapping_add(m_high.wrapping_mul(PRIME64_2));
+            (xxh3_avalanche(low), xxh3_avalanche(high))
+        }
+        17..=128 => {
+            let mut acc = ((len as u64).wrapping_mul(PRIME64_1), 0u64);
+            if len > 32 {
+                if len > 64 {
+                    if len > 96 {
+                        acc = xxh3_mix32b(acc, &input[48..], &input[len - 64..], &secret[96..], seed);
+                    }
+                    acc = xxh3_mix32b(acc, &input[32..], &input[len - 48..], &secret[64..], seed);
+                }
+                acc = xxh3_mix32b(acc, &input[16..], &input[len - 32..], &secret[32..], seed);
+            }
+            acc = xxh3_mix32b(acc, input, &input[len - 16..], secret, seed);
+            xxh3_128_finalize_mid(acc, len, seed)
+        }
+        _ => {
+            let mut acc = ((len as u64).wrapping_mul(PRIME64_1), 0u64);
+            for i in 0..4 {
+                acc = xxh3_mix32b(acc, &input[32 * i..], &input[32 * i + 16..], &secret[32 * i..], seed);
+            }
+            acc = (xxh3_avalanche(acc.0), xxh3_avalanche(acc.1));
+            for i in 4..len / 32 {
+                acc = xxh3_mix32b(
+                    acc,
+                    &input[32 * i..],
+                    &input[32 * i + 16..],
+                    &secret[XXH3_MIDSIZE_STARTOFFSET + 32 * (i - 4)..],
+                    seed,
+                );
+            }
+            acc = xxh3_mix32b(
+                acc,
+                &input[len - 16..],
+                &input[len - 32..],
+                &secret[XXH3_SECRET_SIZE_MIN - XXH3_MIDSIZE_LASTOFFSET - 16..],
+                0u64.wrapping_sub(seed),
+            );
+            xxh3_128_finalize_mid(acc, len, seed)
+        }
+    };
+    ((high as u128) << 64) | low as u128
+}
+
+fn xxh3_128_finalize_mid(acc: (u64, u64), len: usize, seed: u64) -> (u64, u64) {
+    let low = acc.0.wrapping_add(acc.1);
+    let high = acc.0.wrapping_mul(PRIME64_1)
+        .wrapping_add(acc.1.wrapping_mul(PRIME64_4))
+        .wrapping_add((len as u64).wrapping_sub(seed).wrapping_mul(PRIME64_2));
+    (xxh3_avalanche(low), 0u64.wrapping_sub(xxh3_avalanche(high)))
+}
+
+fn xxh3_64_merge(acc: &[u64; 8], secret: &[u8], len: u64) -> u64 {
+    xxh3_merge_accs(acc, &secret[XXH3_SECRET_MERGEACCS_START..], len.wrapping_mul(PRIME64_1))
+}
+
+fn xxh3_128_merge(acc: &[u64; 8], secret: &[u8], len: u64) -> u128 {
+    let low = xxh3_merge_accs(acc, &secret[XXH3_SECRET_MERGEACCS_START..], len.wrapping_mul(PRIME64_1));
+    let high = xxh3_merge_accs(
+        acc,
+        &secret[XXH3_SECRET_SIZE - XXH3_STRIPE_LEN - XXH3_SECRET_MERGEACCS_START..],
+        !len.wrapping_mul(PRIME64_2),
+    );
+    ((high as u128) << 64) | low as u128
+}
+
+// Streaming state shared by the 64-bit and 128-bit variants
+#[derive(Clone)]
+struct Xxh3State {
+    seed: u64,
+    // Derived from a non-zero seed only once stripes have to be consumed; short keys never
+    // read it, so seeded per-key hashers don't pay for it
+    custom_secret: Option<[u8; XXH3_SECRET_SIZE]>,
+    acc: [u64; 8],
+    // Left uninitialized so building a hasher per key (as HashTable does) costs nothing
+    // up front; see `initialized` for which bytes may be read
+    buffer: [MaybeUninit<u8>; XXH3_BUFFER_SIZE],
+    buffer_len: usize,
+    stripes_so_far: usize,
+    total_len: u64,
+}
+
+impl Xxh3State {
+    fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            custom_secret: None,
+            acc: XXH3_INIT_ACC,
+            buffer: [MaybeUninit::uninit(); XXH3_BUFFER_SIZE],
+            buffer_len: 0,
+            stripes_so_far: 0,
+            total_len: 0,
+        }
+    }
+
+    // For a digest: inputs of 241..=256 bytes never consumed a buffer, so the custom secret
+    // may not have been derived yet
+    fn secret(&self) -> Cow<'_, [u8; XXH3_SECRET_SIZE]> {
+        match &self.custom_secret {
+            Some(secret) => Cow::Borrowed(secret),
+            None if self.seed == 0 => Cow::Borrowed(&XXH3_DEFAULT_SECRET),
+            None => Cow::Owned(xxh3_custom_secret(self.seed)),
+        }
+    }
+
+    // The buffer is filled from the front and only consumed once full, so its first
+    // `buffer_len` bytes, or all of it once anything was consumed, are initialized
+    fn initialized(&self) -> &[u8] {
+        let len = if self.total_len > self.buffer_len as u64 { XXH3_BUFFER_SIZE } else { self.buffer_len };
+        // SAFETY: see above; MaybeUninit<u8> has the same layout as u8
+        unsafe { std::slice::from_raw_parts(self.buffer.as_ptr().cast::<u8>(), len) }
+    }
+
+    // The whole input of a short (<= 240 byte) hash, to be hashed in one shot
+    fn short_input(&self) -> &[u8] {
+        &self.initialized()[..self.total_len as usize]
+    }
+
+    #[inline]
+    fn fill_buffer(&mut self, input: &[u8]) {
+        for (slot, &byte) in self.buffer[self.buffer_len..].iter_mut().zip(input) {
+            slot.write(byte);
+        }
+        self.buffer_len += input.len();
+    }
+
+    #[inline]
+    fn write(&mut self, input: &[u8]) {
+        self.total_len += input.len() as u64;
+
+        // Short keys always fit: one copy, no stripes and no secret involved
+        if input.len() <= XXH3_BUFFER_SIZE - self.buffer_len {
+            self.fill_buffer(input);
+        } else {
+            self.write_long(input);
+        }
+    }
+
+    #[cold]
+    fn write_long(&mut self, mut input: &[u8]) {
+        while !input.is_empty() {
+            // Only consume a full buffer once more input arrives, so the digest always
+            // has the final stripe available
+            if self.buffer_len == XXH3_BUFFER_SIZE {
+                if self.seed != 0 && self.custom_secret.is_none() {
+                    self.custom_secret = Some(xxh3_custom_secret(self.seed));
+                }
+                let secret = self.custom_secret.as_ref().unwrap_or(&XXH3_DEFAULT_SECRET);
+                // SAFETY: a full buffer is entirely initialized
+                let buffer = unsafe { std::slice::from_raw_parts(self.buffer.as_ptr().cast::<u8>(), XXH3_BUFFER_SIZE) };
+                xxh3_consume_stripes(&mut self.acc, &mut self.stripes_so_far, buffer, XXH3_BUFFER_STRIPES, secret);
+                self.buffer_len = 0;
+            }
+
+            let fill = (XXH3_BUFFER_SIZE - self.buffer_len).min(input.len());
+            self.fill_buffer(&input[..fill]);
+            input = &input[fill..];
+        }
+    }
+
+    // Accumulators for a long (> 240 byte) input, including the buffered tail
+    fn long_acc(&self, secret: &[u8; XXH3_SECRET_SIZE]) -> [u64; 8] {
+        let buffer = self.initialized();
+        let mut acc = self.acc;
+        let mut stripes_so_far = self.stripes_so_far;
+        let mut last_stripe = [0u8; XXH3_STRIPE_LEN];
+
+        if self.buffer_len >= XXH3_STRIPE_LEN {
+            let stripe_count = (self.buffer_len - 1) / XXH3_STRIPE_LEN;
+            xxh3_consume_stripes(&mut acc, &mut stripes_so_far, buffer, stripe_count, secret);
+            last_stripe.copy_from_slice(&buffer[self.buffer_len - XXH3_STRIPE_LEN..self.buffer_len]);
+        } else {
+            // The tail of the previously consumed buffer completes the last stripe
+            let carried = XXH3_STRIPE_LEN - self.buffer_len;
+            last_stripe[..carried].copy_from_slice(&buffer[XXH3_BUFFER_SIZE - carried..]);
+            last_stripe[carried..].copy_from_slice(&buffer[..self.buffer_len]);
+        }
+
+        xxh3_accumulate_512(&mut acc, &last_stripe, &secret[XXH3_SECRET_SIZE - XXH3_STRIPE_LEN - XXH3_SECRET_LASTACC_START..]);
+        acc
+    }
+
+    fn is_short(&self) -> bool {
+        self.total_len as usize <= XXH3_MIDSIZE_MAX
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone)]
+pub struct Xxh3_64 {
+    state: Xxh3State,
+}
+
+impl Xxh3_64 {
+    pub fn new(seed: u64) -> Self {
+        Self { state: Xxh3State::new(seed) }
+    }
+
+    pub fn write(&mut self, input: &[u8]) {
+        self.state.write(input);
+    }
+
+    pub fn finish(&self) -> u64 {
+        if self.state.is_short() {
+            return xxh3_64_short(self.state.short_input(), &XXH3_DEFAULT_SECRET, self.state.seed);
+        }
+        let secret = self.state.secret();
+        xxh3_64_merge(&self.state.long_acc(&secret), &*secret, self.state.total_len)
+    }
+
+    pub fn oneshot(input: &[u8], seed: u64) -> u64 {
+        if input.len() <= XXH3_MIDSIZE_MAX {
+            return xxh3_64_short(input, &XXH3_DEFAULT_SECRET, seed);
+        }
+        let secret = if seed == 0 { XXH3_DEFAULT_SECRET } else { xxh3_custom_secret(seed) };
+        xxh3_64_merge(&xxh3_hash_long_acc(input, &secret), &secret, input.len() as u64)
+    }
+}
+
+impl Default for Xxh3_64 {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Hasher for Xxh3_64 {
+    fn write(&mut self, bytes: &[u8]) {
+        self.write(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        self.finish()
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone)]
+pub struct Xxh3_128 {
+    state: Xxh3State,
+}
+
+impl Xxh3_128 {
+    pub fn new(seed: u64) -> Self {
+        Self { state: Xxh3State::new(seed) }
+    }
+
+    pub fn write(&mut self, input: &[u8]) {
+        self.state.write(input);
+    }
+
+    pub fn finish(&self) -> u128 {
+        if self.state.is_short() {
+            return xxh3_128_short(self.state.short_input(), &XXH3_DEFAULT_SECRET, self.state.seed);
+        }
+        let secret = self.state.secret();
+        xxh3_128_merge(&self.state.long_acc(&secret), &*secret, self.state.total_len)
+    }
+
+    pub fn oneshot(input: &[u8], seed: u64) -> u128 {
+        if input.len() <= XXH3_MIDSIZE_MAX {
+            return xxh3_128_short(input, &XXH3_DEFAULT_SECRET, seed);
+        }
+        let secret = if seed == 0 { XXH3_DEFAULT_SECRET } else { xxh3_custom_secret(seed) };
+        xxh3_128_merge(&xxh3_hash_long_acc(input, &secret), &secret, input.len() as u64)
+    }
+}
+
+impl Default for Xxh3_128 {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Hasher for Xxh3_128 {
+    fn write(&mut self, bytes: &[u8]) {
+        self.write(bytes);
+    }
+
+    // Hasher can only expose 64 bits, so this is the low half of the 128-bit digest
+    fn finish(&self) -> u64 {
+        self.finish() as u64
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Xxh3_64Builder {
+    seed: u64,
+}
+
+impl Xxh3_64Builder {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl BuildHasher for Xxh3_64Builder {
+    type Hasher = Xxh3_64;
+
+    fn build_hasher(&self) -> Xxh3_64 {
+        Xxh3_64::new(self.seed)
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Xxh3_128Builder {
+    seed: u64,
+}
+
+impl Xxh3_128Builder {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl BuildHasher for Xxh3_128Builder {
+    type Hasher = Xxh3_128;
+
+    fn build_hasher(&self) -> Xxh3_128 {
+        Xxh3_128::new(self.seed)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -343,4 +1012,152 @@ mod tests {
         hasher64.write(b"test");
         let _result64 = hasher64.finish();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_build_hasher() {
+        let input = b"Nobody inspects the spammish repetition";
+
+        let mut hasher32 = XxHash32Builder::new(0x9E3779B1).build_hasher();
+        hasher32.write(input);
+        assert_eq!(hasher32.finish(), XxHash32::oneshot(input, 0x9E3779B1));
+
+        let mut hasher64 = XxHash64Builder::default().build_hasher();
+        hasher64.write(input);
+        assert_eq!(hasher64.finish(), 0xFBCEA83C8A378BF1);
+
+        assert_eq!(XxHash64Builder::new(1).hash_one("Abha"), XxHash64Builder::new(1).hash_one("Abha"));
+        assert_ne!(XxHash64Builder::new(1).hash_one("Abha"), XxHash64Builder::new(2).hash_one("Abha"));
+    }
+
+    // Input generator used by the reference sanity checks in xxHash's test suite
+    fn sanity_buffer(len: usize) -> Vec<u8> {
+        let mut byte_gen: u64 = 2654435761;
+        (0..len)
+            .map(|_| {
+                let byte = (byte_gen >> 56) as u8;
+                byte_gen = byte_gen.wrapping_mul(11400714785074694797);
+                byte
+            })
+            .collect()
+    }
+
+    const SANITY_PRIME32: u64 = 2654435761;
+    const SANITY_PRIME64: u64 = 11400714785074694797;
+
+    #[test]
+    fn test_xxh3_64_test_vectors() {
+        let buffer = sanity_buffer(2367);
+        let vectors: [(usize, u64, u64); 26] = [
+            (0, 0, 0x2D06800538D394C2),
+            (0, SANITY_PRIME64, 0xA8A6B918B2F0364A),
+            (1, 0, 0xC44BDFF4074EECDB),
+            (1, SANITY_PRIME64, 0x032BE332DD766EF8),
+            (6, 0, 0x27B56A84CD2D7325),
+            (6, SANITY_PRIME64, 0x84589C116AB59AB9),
+            (12, 0, 0xA713DAF0DFBB77E7),
+            (12, SANITY_PRIME64, 0xE7303E1B2336DE0E),
+            (24, 0, 0xA3FE70BF9D3510EB),
+            (24, SANITY_PRIME64, 0x850E80FC35BDD690),
+            (48, 0, 0x397DA259ECBA1F11),
+            (48, SANITY_PRIME64, 0xADC2CBAA44ACC616),
+            (80, 0, 0xBCDEFBBB2C47C90A),
+            (80, SANITY_PRIME64, 0xC6DD0CB699532E73),
+            (195, 0, 0xCD94217EE362EC3A),
+            (195, SANITY_PRIME64, 0xBA68003D370CB3D9),
+            (403, 0, 0xCDEB804D65C6DEA4),
+            (403, SANITY_PRIME64, 0x6259F6ECFD6443FD),
+            (512, 0, 0x617E49599013CB6B),
+            (512, SANITY_PRIME64, 0x3CE457DE14C27708),
+            (2048, 0, 0xDD59E2C3A5F038E0),
+            (2048, SANITY_PRIME64, 0x66F81670669ABABC),
+            (2240, 0, 0x6E73A90539CF2948),
+            (2240, SANITY_PRIME64, 0x757BA8487D1B5247),
+            (2367, 0, 0xCB37AEB9E5D361ED),
+            (2367, SANITY_PRIME64, 0xD2DB3415B942B42A),
+        ];
+
+        for (len, seed, expected) in vectors {
+            assert_eq!(Xxh3_64::oneshot(&buffer[..len], seed), expected, "len {} seed {}", len, seed);
+        }
+    }
+
+    #[test]
+    fn test_xxh3_128_test_vectors() {
+        let buffer = sanity_buffer(2367);
+        let vectors: [(usize, u64, u128); 17] = [
+            (0, 0, 0x99AA06D3014798D86001C324468D497F),
+            (0, SANITY_PRIME32, 0x92220AE55E14AB505444F7869C671AB0),
+            (1, 0, 0xA6CD5E9392000F6AC44BDFF4074EECDB),
+            (1, SANITY_PRIME32, 0x89B99554BA22467CB53D5557E7F76F8D),
+            (6, 0, 0x082AFE0B8162D12A3E7039BDDA43CFC6),
+            (6, SANITY_PRIME32, 0x5A865B5389ABD2B1269D8F70BE98856E),
+            (12, 0, 0x6E3EFD8FC7802B18061A192713F69AD9),
+            (12, SANITY_PRIME32, 0xD7E09D518A3405D39BE9F9A67F3C7DFB),
+            (24, 0, 0x0CE966E4678D37611E7044D28B1B901D),
+            (24, SANITY_PRIME32, 0x3162026714A6A243D7304C54EBAD40A9),
+            (48, 0, 0xA002AC4E5478227EF942219AED80F67B),
+            (48, SANITY_PRIME32, 0x163ADDE36C0722957BA3C3E453A1934E),
+            (81, 0, 0x4952F58181AB00425E8BAFB9F95FB803),
+            (81, SANITY_PRIME32, 0x2724EC7ADC750FB6703FBB3D7A5F755C),
+            (222, 0, 0x337E09641B948717F1AEBD597CEC6B3A),
+            (222, SANITY_PRIME32, 0x91820016621E97F1AE995BB8AF917A8D),
+            (403, 0, 0x1B6DE21E332DD73DCDEB804D65C6DEA4),
+        ];
+
+        for (len, seed, expected) in vectors {
+            assert_eq!(Xxh3_128::oneshot(&buffer[..len], seed), expected, "len {} seed {}", len, seed);
+        }
+    }
+
+    #[test]
+    fn test_xxh3_streaming_matches_oneshot() {
+        let buffer = sanity_buffer(2367);
+
+        for len in (0..=buffer.len()).step_by(7).chain([240, 241, 255, 256, 257, 1024, 1025, 2048]) {
+            for seed in [0, SANITY_PRIME64] {
+                for chunk_size in [1, 13, 64, 256, 1000] {
+                    let mut hasher64 = Xxh3_64::new(seed);
+                    let mut hasher128 = Xxh3_128::new(seed);
+                    for chunk in buffer[..len].chunks(chunk_size) {
+                        hasher64.write(chunk);
+                        hasher128.write(chunk);
+                    }
+
+                    assert_eq!(hasher64.finish(), Xxh3_64::oneshot(&buffer[..len], seed), "len {} chunk {}", len, chunk_size);
+                    assert_eq!(hasher128.finish(), Xxh3_128::oneshot(&buffer[..len], seed), "len {} chunk {}", len, chunk_size);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_xxh3_custom_secret_is_derived_lazily() {
+        let buffer = sanity_buffer(600);
+        let mut hasher = Xxh3_64::new(SANITY_PRIME64);
+
+        hasher.write(&buffer[..256]);
+        assert!(hasher.state.custom_secret.is_none());
+        assert_eq!(hasher.finish(), Xxh3_64::oneshot(&buffer[..256], SANITY_PRIME64));
+
+        hasher.write(&buffer[256..]);
+        assert!(hasher.state.custom_secret.is_some());
+        assert_eq!(hasher.finish(), Xxh3_64::oneshot(&buffer, SANITY_PRIME64));
+    }
+
+    #[test]
+    fn test_xxh3_hasher_trait() {
+        let input = b"Nobody inspects the spammish repetition";
+
+        let mut hasher64 = Xxh3_64Builder::default().build_hasher();
+        Hasher::write(&mut hasher64, input);
+        assert_eq!(Hasher::finish(&hasher64), Xxh3_64::oneshot(input, 0));
+
+        let mut hasher128 = Xxh3_128Builder::new(42).build_hasher();
+        Hasher::write(&mut hasher128, input);
+        assert_eq!(Hasher::finish(&hasher128), Xxh3_128::oneshot(input, 42) as u64);
+
+        // A str key is written as its bytes plus a 0xff terminator, one hasher per key
+        assert_eq!(Xxh3_64Builder::default().hash_one("Hamburg"), Xxh3_64::oneshot(b"Hamburg\xff", 0));
+        assert_eq!(Xxh3_64Builder::new(42).hash_one("Hamburg"), Xxh3_64::oneshot(b"Hamburg\xff", 42));
+    }
+}